@@ -0,0 +1,358 @@
+pub mod synth;
+pub mod systems;
+
+use crate::game::{GameEventKind, GameState};
+use crate::AppState;
+use bevy::audio::{AddAudioSource, PlaybackMode, Volume, VolumeLevel};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use synth::{Synth, SynthParams, SynthPreset, SynthSound, Waveform};
+use systems::*;
+
+/// Volume of the music before the volume of its bus is applied.
+const MUSIC_BASE_VOLUME: f32 = 0.5;
+
+/// Factor applied to the music while the game is paused or between two waves.
+const MUSIC_DUCK: f32 = 0.4;
+
+/// How much of the full volume the ducking of the music changes per second.
+const DUCK_SPEED: f32 = 2.0;
+
+/// Short click of the buttons of the menus, synthesized when their sounds are missing.
+const BLIP: SynthParams = SynthParams {
+    waveform: Waveform::Sine,
+    frequency: 880.0,
+    slide: 0.0,
+    min_frequency: 0.0,
+    duty: 0.5,
+    attack: 0.0,
+    sustain: 0.02,
+    punch: 0.0,
+    decay: 0.04,
+    vibrato_depth: 0.0,
+    vibrato_speed: 0.0,
+    volume: 0.4,
+};
+
+/// Group of sounds sharing a volume setting.
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+}
+
+/// Volume of a sound on its own, before the volumes of its bus and the master volume.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BaseVolume(pub f32);
+
+/// Sound effect paused along with the game, to resume along with it.
+#[derive(Component)]
+pub struct PausedWithGame;
+
+/// Volumes of the buses, from the settings of the player, the mute toggle and the
+/// ducking of the music. Every sound is spawned through it.
+#[derive(Resource, Debug)]
+pub struct AudioMixer {
+    /// Between 0 and 1.
+    pub master: f32,
+    /// Between 0 and 1.
+    pub music: f32,
+    /// Between 0 and 1.
+    pub sfx: f32,
+    pub muted: bool,
+    /// Factor applied to the music, lowered while the game is paused or between waves.
+    pub duck: f32,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+            muted: false,
+            duck: 1.0,
+        }
+    }
+}
+
+impl AudioMixer {
+    /// Factor applied to the base volume of the sounds of `bus`.
+    pub fn gain(&self, bus: AudioBus) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        let bus_volume = match bus {
+            AudioBus::Music => MUSIC_BASE_VOLUME * self.music * self.duck,
+            AudioBus::Sfx => self.sfx,
+        };
+        self.master * bus_volume
+    }
+
+    /// Playback of a sound of `bus`, the volume of `settings` being its base volume. The
+    /// sound itself, an audio file or a synthesized one, is spawned along with it.
+    fn sound(&self, bus: AudioBus, settings: PlaybackSettings) -> impl Bundle {
+        let base = match settings.volume {
+            Volume::Relative(level) | Volume::Absolute(level) => level.get(),
+        };
+        let volume = Volume::Absolute(VolumeLevel::new(base * self.gain(bus)));
+        (
+            PlaybackSettings { volume, ..settings },
+            bus,
+            BaseVolume(base),
+        )
+    }
+
+    pub fn music(&self, settings: PlaybackSettings) -> impl Bundle {
+        self.sound(AudioBus::Music, settings)
+    }
+
+    pub fn sfx(&self, settings: PlaybackSettings) -> impl Bundle {
+        self.sound(AudioBus::Sfx, settings)
+    }
+
+    /// Sound effect panned between the speakers according to where it is played on the
+    /// play field.
+    pub fn positional_sfx(&self, settings: PlaybackSettings, translation: Vec3) -> impl Bundle {
+        (
+            self.sfx(settings.with_spatial(true)),
+            TransformBundle::from_transform(Transform::from_translation(translation)),
+        )
+    }
+}
+
+/// Sounds played when an event happens.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundCue {
+    /// Paths in the assets folder, played in turn each time the event happens. An
+    /// empty list silences the event.
+    pub sounds: Vec<String>,
+    /// Sounds synthesized in place of the files when some are missing, or on their own
+    /// when there are no files.
+    pub synth: Vec<Synth>,
+    /// Base volume of the sounds, 1 being their own volume.
+    pub volume: f32,
+}
+
+impl Default for SoundCue {
+    fn default() -> Self {
+        Self {
+            sounds: Vec::new(),
+            synth: Vec::new(),
+            volume: 1.0,
+        }
+    }
+}
+
+impl SoundCue {
+    fn new(sounds: &[&str], synth: &[Synth], volume: f32) -> Self {
+        Self {
+            sounds: sounds.iter().map(|sound| sound.to_string()).collect(),
+            synth: synth.to_vec(),
+            volume,
+        }
+    }
+}
+
+/// The sound cue of each kind of event, the sound pack of the game.
+///
+/// Events left out of a config file keep their default cue.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(
+    from = "BTreeMap<GameEventKind, SoundCue>",
+    into = "BTreeMap<GameEventKind, SoundCue>"
+)]
+pub struct SoundCues(pub BTreeMap<GameEventKind, SoundCue>);
+
+impl Default for SoundCues {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (
+                GameEventKind::Shot,
+                SoundCue::new(
+                    &["audio/shoot.ogg"],
+                    &[Synth::Preset(SynthPreset::Shoot)],
+                    1.0,
+                ),
+            ),
+            (
+                GameEventKind::AlienKilled,
+                SoundCue::new(
+                    &["audio/invaderkilled.ogg"],
+                    &[Synth::Preset(SynthPreset::InvaderKilled)],
+                    1.0,
+                ),
+            ),
+            (
+                GameEventKind::PlayerKilled,
+                SoundCue::new(
+                    &["audio/explosion.ogg"],
+                    &[Synth::Preset(SynthPreset::Explosion)],
+                    1.0,
+                ),
+            ),
+            (
+                GameEventKind::UfoSpawned,
+                SoundCue::new(
+                    &["audio/ufo_highpitch.ogg"],
+                    &[Synth::Preset(SynthPreset::Ufo)],
+                    0.6,
+                ),
+            ),
+            (
+                GameEventKind::UfoLeaving,
+                SoundCue::new(
+                    &["audio/ufo_lowpitch.ogg"],
+                    &[Synth::Preset(SynthPreset::UfoLow)],
+                    0.6,
+                ),
+            ),
+            (
+                GameEventKind::FormationStep,
+                SoundCue::new(
+                    &[
+                        "audio/fastinvader1.ogg",
+                        "audio/fastinvader2.ogg",
+                        "audio/fastinvader3.ogg",
+                        "audio/fastinvader4.ogg",
+                    ],
+                    &[
+                        Synth::Preset(SynthPreset::InvaderStep(0)),
+                        Synth::Preset(SynthPreset::InvaderStep(1)),
+                        Synth::Preset(SynthPreset::InvaderStep(2)),
+                        Synth::Preset(SynthPreset::InvaderStep(3)),
+                    ],
+                    0.7,
+                ),
+            ),
+            (
+                GameEventKind::ButtonHovered,
+                SoundCue::new(&["audio/hovered.ogg"], &[Synth::Custom(BLIP)], 1.0),
+            ),
+            (
+                GameEventKind::ButtonPressed,
+                SoundCue::new(
+                    &["audio/pressed.ogg"],
+                    &[Synth::Custom(SynthParams {
+                        frequency: 660.0,
+                        ..BLIP
+                    })],
+                    1.0,
+                ),
+            ),
+        ]))
+    }
+}
+
+impl From<BTreeMap<GameEventKind, SoundCue>> for SoundCues {
+    fn from(cues: BTreeMap<GameEventKind, SoundCue>) -> Self {
+        let mut sound_cues = Self::default();
+        sound_cues.0.extend(cues);
+        sound_cues
+    }
+}
+
+impl From<SoundCues> for BTreeMap<GameEventKind, SoundCue> {
+    fn from(sound_cues: SoundCues) -> Self {
+        sound_cues.0
+    }
+}
+
+/// A sound of a cue, read from a file or synthesized.
+#[derive(Clone)]
+pub enum CueSound {
+    File(Handle<AudioSource>),
+    Synth(Handle<SynthSound>),
+}
+
+impl CueSound {
+    /// Spawn the sound along with `bundle`, its playback settings among others.
+    pub fn spawn(&self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        match self {
+            CueSound::File(handle) => commands.spawn((handle.clone(), bundle)).id(),
+            CueSound::Synth(handle) => commands.spawn((handle.clone(), bundle)).id(),
+        }
+    }
+}
+
+/// The sounds of a cue, loaded, and the one to play next.
+pub struct LoadedCue {
+    sounds: Vec<CueSound>,
+    volume: f32,
+    next: usize,
+}
+
+impl LoadedCue {
+    /// Settings of the next sound, and the sound itself unless the cue is silent.
+    pub fn next_sound(&mut self, mode: PlaybackMode) -> Option<(CueSound, PlaybackSettings)> {
+        let sound = self.sounds.get(self.next)?.clone();
+        self.next = (self.next + 1) % self.sounds.len();
+        let settings = PlaybackSettings {
+            mode,
+            volume: Volume::Relative(VolumeLevel::new(self.volume)),
+            ..default()
+        };
+        Some((sound, settings))
+    }
+}
+
+/// The sound cues of the config, with their sounds loaded.
+#[derive(Resource, Default)]
+pub struct LoadedCues(pub BTreeMap<GameEventKind, LoadedCue>);
+
+pub struct MixerPlugin;
+
+impl Plugin for MixerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<SynthSound>()
+            .init_resource::<AudioMixer>()
+            .add_systems(Startup, load_sound_cues)
+            .add_systems(Update, play_sound_cues.before(apply_mixer))
+            .add_systems(
+                Update,
+                (
+                    set_bus_volumes,
+                    // Letters are typed on the name entry screen.
+                    toggle_mute.run_if(not(in_state(AppState::NameEntry))),
+                    duck_music,
+                    apply_mixer.run_if(resource_changed::<AudioMixer>()),
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(GameState::Pause), pause_sounds)
+            .add_systems(OnExit(GameState::Pause), resume_sounds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sound_cues_round_trip() {
+        let mut sound_cues = SoundCues::default();
+        sound_cues.0.insert(
+            GameEventKind::Shot,
+            SoundCue::new(
+                &["audio/laser.ogg"],
+                &[Synth::Preset(SynthPreset::Shoot)],
+                0.8,
+            ),
+        );
+        let contents = ron::to_string(&sound_cues).unwrap();
+        assert!(contents.starts_with('{'), "{contents}");
+        assert_eq!(ron::from_str::<SoundCues>(&contents).unwrap(), sound_cues);
+    }
+
+    #[test]
+    fn sound_cues_keep_defaults() {
+        let sound_cues: SoundCues = ron::from_str("{ Shot: (sounds: []) }").unwrap();
+        assert!(sound_cues.0[&GameEventKind::Shot].sounds.is_empty());
+        assert_eq!(
+            sound_cues.0[&GameEventKind::AlienKilled],
+            SoundCues::default().0[&GameEventKind::AlienKilled]
+        );
+    }
+}
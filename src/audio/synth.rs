@@ -0,0 +1,400 @@
+use bevy::audio::{Decodable, Source};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Longest sound that may be synthesized, in seconds.
+pub const MAX_SYNTH_DURATION: f32 = 10.0;
+
+/// Shape of the oscillator of a synthesized sound.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Waveform {
+    Square,
+    Sawtooth,
+    Sine,
+    /// A new random value on every period, lower frequencies rumble and higher ones hiss.
+    Noise,
+}
+
+/// Parameters of a sound synthesized the way sfxr does: an oscillator sliding in pitch,
+/// shaped by an attack, sustain and decay envelope.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SynthParams {
+    pub waveform: Waveform,
+    /// Starting pitch, in hertz.
+    pub frequency: f32,
+    /// Change of pitch, in octaves per second.
+    pub slide: f32,
+    /// The sound stops once its pitch slides under this one, in hertz.
+    pub min_frequency: f32,
+    /// Share of each period spent high by the square wave, between 0 and 1.
+    pub duty: f32,
+    /// Seconds to rise to the full volume.
+    pub attack: f32,
+    /// Seconds at the full volume.
+    pub sustain: f32,
+    /// Extra volume at the start of the sustain, fading until its end.
+    pub punch: f32,
+    /// Seconds to fade out.
+    pub decay: f32,
+    /// Share of the pitch the vibrato moves it by.
+    pub vibrato_depth: f32,
+    /// Vibrato cycles per second.
+    pub vibrato_speed: f32,
+    /// Between 0 and 1.
+    pub volume: f32,
+}
+
+impl Default for SynthParams {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Square,
+            frequency: 440.0,
+            slide: 0.0,
+            min_frequency: 0.0,
+            duty: 0.5,
+            attack: 0.0,
+            sustain: 0.1,
+            punch: 0.0,
+            decay: 0.1,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            volume: 0.5,
+        }
+    }
+}
+
+/// Ready made sounds of the game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum SynthPreset {
+    Shoot,
+    Explosion,
+    InvaderKilled,
+    /// One of the four notes of the marching aliens, from 0 to 3.
+    InvaderStep(u8),
+    /// Loop of a mystery ship.
+    Ufo,
+    /// Loop of a mystery ship leaving the screen.
+    UfoLow,
+}
+
+impl SynthParams {
+    /// Length of the envelope, in seconds. The sound may stop earlier if its pitch slides
+    /// under the minimum.
+    pub fn duration(&self) -> f32 {
+        self.attack.max(0.0) + self.sustain.max(0.0) + self.decay.max(0.0)
+    }
+}
+
+impl SynthPreset {
+    pub fn params(self) -> SynthParams {
+        match self {
+            SynthPreset::Shoot => SynthParams {
+                frequency: 1200.0,
+                slide: -3.0,
+                min_frequency: 100.0,
+                duty: 0.25,
+                sustain: 0.05,
+                punch: 0.2,
+                decay: 0.15,
+                volume: 0.3,
+                ..default()
+            },
+            SynthPreset::Explosion => SynthParams {
+                waveform: Waveform::Noise,
+                frequency: 2000.0,
+                slide: -1.5,
+                sustain: 0.1,
+                punch: 0.5,
+                decay: 0.6,
+                volume: 0.6,
+                ..default()
+            },
+            SynthPreset::InvaderKilled => SynthParams {
+                waveform: Waveform::Noise,
+                frequency: 5000.0,
+                slide: -3.0,
+                sustain: 0.05,
+                punch: 0.3,
+                decay: 0.2,
+                volume: 0.5,
+                ..default()
+            },
+            SynthPreset::InvaderStep(note) => {
+                const NOTES: [f32; 4] = [110.0, 98.0, 87.0, 82.0];
+                SynthParams {
+                    frequency: NOTES[note as usize % NOTES.len()],
+                    slide: -0.5,
+                    sustain: 0.06,
+                    decay: 0.08,
+                    volume: 0.5,
+                    ..default()
+                }
+            }
+            // Whole vibrato cycles, so that the sound loops without a click.
+            SynthPreset::Ufo | SynthPreset::UfoLow => SynthParams {
+                waveform: Waveform::Sawtooth,
+                frequency: if self == SynthPreset::Ufo {
+                    700.0
+                } else {
+                    350.0
+                },
+                sustain: 0.5,
+                decay: 0.0,
+                vibrato_depth: 0.15,
+                vibrato_speed: 8.0,
+                volume: 0.3,
+                ..default()
+            },
+        }
+    }
+}
+
+/// A preset or parameters, as written in a sound cue.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Synth {
+    Preset(SynthPreset),
+    Custom(SynthParams),
+}
+
+impl Synth {
+    pub fn params(&self) -> SynthParams {
+        match self {
+            Synth::Preset(preset) => preset.params(),
+            Synth::Custom(params) => params.clone(),
+        }
+    }
+}
+
+/// Sound synthesized from parameters instead of decoded from an audio file.
+#[derive(Asset, TypePath, Clone)]
+pub struct SynthSound {
+    samples: Arc<[f32]>,
+}
+
+impl SynthSound {
+    pub fn new(params: &SynthParams) -> Self {
+        Self {
+            samples: render(params).into(),
+        }
+    }
+}
+
+impl Decodable for SynthSound {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            samples: self.samples.clone(),
+            index: 0,
+        }
+    }
+}
+
+pub struct SynthDecoder {
+    samples: Arc<[f32]>,
+    index: usize,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.index).copied();
+        self.index += 1;
+        sample
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.index))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+/// Mono samples of the sound described by `params`.
+fn render(params: &SynthParams) -> Vec<f32> {
+    let attack = params.attack.max(0.0);
+    let sustain = params.sustain.max(0.0);
+    let decay = params.decay.max(0.0);
+    let length = (params.duration().min(MAX_SYNTH_DURATION) * SAMPLE_RATE as f32) as usize;
+
+    // Noise is the same on every run, like the rest of the sounds.
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut noise = rng.gen_range(-1.0..1.0);
+    let mut phase = 0.0;
+    let mut samples = Vec::with_capacity(length);
+
+    for i in 0..length {
+        let t = i as f32 / SAMPLE_RATE as f32;
+
+        let envelope = if t < attack {
+            t / attack
+        } else if t < attack + sustain {
+            1.0 + params.punch * (1.0 - (t - attack) / sustain)
+        } else {
+            1.0 - (t - attack - sustain) / decay
+        };
+
+        let frequency = params.frequency * (params.slide * t).exp2();
+        if frequency < params.min_frequency {
+            break;
+        }
+        let vibrato = 1.0 + params.vibrato_depth * (TAU * params.vibrato_speed * t).sin();
+
+        phase += frequency * vibrato / SAMPLE_RATE as f32;
+        if phase >= 1.0 {
+            phase %= 1.0;
+            noise = rng.gen_range(-1.0..1.0);
+        }
+
+        let wave = match params.waveform {
+            Waveform::Square if phase < params.duty => 1.0,
+            Waveform::Square => -1.0,
+            Waveform::Sawtooth => 1.0 - 2.0 * phase,
+            Waveform::Sine => (TAU * phase).sin(),
+            Waveform::Noise => noise,
+        };
+        samples.push((wave * envelope * params.volume).clamp(-1.0, 1.0));
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(attack: f32, sustain: f32, decay: f32) -> SynthParams {
+        SynthParams {
+            attack,
+            sustain,
+            decay,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn duration_is_the_envelope() {
+        assert_eq!(square(0.25, 0.5, 0.25).duration(), 1.0);
+        assert_eq!(square(-1.0, 0.5, 0.0).duration(), 0.5);
+        assert_eq!(square(0.0, 0.0, 0.0).duration(), 0.0);
+    }
+
+    #[test]
+    fn sample_count_matches_duration() {
+        assert_eq!(render(&square(0.25, 0.5, 0.25)).len(), SAMPLE_RATE as usize);
+        assert_eq!(
+            render(&square(0.0, 0.0, 0.5)).len(),
+            SAMPLE_RATE as usize / 2
+        );
+        assert!(render(&square(0.0, 0.0, 0.0)).is_empty());
+        // Too long sounds are cut.
+        assert_eq!(
+            render(&square(0.0, 2.0 * MAX_SYNTH_DURATION, 0.0)).len(),
+            (MAX_SYNTH_DURATION * SAMPLE_RATE as f32) as usize
+        );
+    }
+
+    #[test]
+    fn sliding_under_the_minimum_stops_the_sound() {
+        let params = SynthParams {
+            frequency: 800.0,
+            slide: -4.0,
+            min_frequency: 400.0,
+            sustain: 1.0,
+            decay: 0.0,
+            ..default()
+        };
+        // One octave down takes a quarter of a second.
+        let samples = render(&params).len() as f32 / SAMPLE_RATE as f32;
+        assert!((samples - 0.25).abs() < 0.01, "{samples}");
+    }
+
+    #[test]
+    fn zero_attack_and_decay_hold_the_volume() {
+        let params = SynthParams {
+            volume: 0.5,
+            ..square(0.0, 0.5, 0.0)
+        };
+        let samples = render(&params);
+        assert_eq!(samples[0], 0.5);
+        assert!(samples.iter().all(|sample| sample.abs() == 0.5));
+    }
+
+    #[test]
+    fn attack_and_decay_ramp_the_volume() {
+        let params = SynthParams {
+            waveform: Waveform::Sine,
+            volume: 1.0,
+            ..square(0.5, 0.0, 0.5)
+        };
+        let samples = render(&params);
+        let half = samples.len() / 2;
+        let peak = |range: &[f32]| {
+            range
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()))
+        };
+        assert_eq!(samples[0], 0.0);
+        assert!(peak(&samples[..half / 4]) < 0.3);
+        assert!(peak(&samples[half - half / 8..half + half / 8]) > 0.9);
+        assert!(peak(&samples[samples.len() - half / 4..]) < 0.3);
+    }
+
+    #[test]
+    fn samples_stay_in_range() {
+        let presets = [
+            SynthPreset::Shoot,
+            SynthPreset::Explosion,
+            SynthPreset::InvaderKilled,
+            SynthPreset::InvaderStep(0),
+            SynthPreset::InvaderStep(3),
+            SynthPreset::Ufo,
+            SynthPreset::UfoLow,
+        ];
+        let loud = SynthParams {
+            waveform: Waveform::Noise,
+            punch: 5.0,
+            volume: 1.0,
+            ..default()
+        };
+        let all_params = presets.map(SynthPreset::params).into_iter().chain([loud]);
+        for params in all_params {
+            let samples = render(&params);
+            assert!(!samples.is_empty(), "{params:?}");
+            assert!(
+                samples.iter().all(|sample| (-1.0..=1.0).contains(sample)),
+                "{params:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rendering_is_deterministic() {
+        let params = SynthPreset::Explosion.params();
+        assert_eq!(render(&params), render(&params));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::audio::*;
+use crate::config::GameConfig;
+use crate::game::input::{Action, ActiveGamepad};
+use crate::game::GameEvent;
+use crate::storage;
+use crate::user_settings::UserSettings;
+use bevy::audio::SpatialAudioSink;
+
+pub fn set_bus_volumes(settings: Res<UserSettings>, mut mixer: ResMut<AudioMixer>) {
+    if settings.is_changed() {
+        mixer.master = settings.master_volume;
+        mixer.music = settings.music_volume;
+        mixer.sfx = settings.sfx_volume;
+    }
+}
+
+pub fn toggle_mute(
+    keyboard_input: Res<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+    active_gamepad: Res<ActiveGamepad>,
+    config: Res<GameConfig>,
+    mut mixer: ResMut<AudioMixer>,
+) {
+    let mute_button = active_gamepad.0.is_some_and(|gamepad| {
+        config
+            .gamepad_bindings
+            .just_pressed(Action::Mute, gamepad, &button_input)
+    });
+    if config
+        .key_bindings
+        .just_pressed(Action::Mute, &keyboard_input)
+        || mute_button
+    {
+        mixer.muted = !mixer.muted;
+    }
+}
+
+pub fn duck_music(
+    app_state: Res<State<AppState>>,
+    game_state: Res<State<GameState>>,
+    time: Res<Time>,
+    mut mixer: ResMut<AudioMixer>,
+) {
+    let ducked = *app_state.get() == AppState::InGame
+        && matches!(game_state.get(), GameState::Pause | GameState::Transition);
+    let target = if ducked { MUSIC_DUCK } else { 1.0 };
+
+    // Fade towards the target, only touching the mixer while it changes.
+    let step = DUCK_SPEED * time.delta_seconds();
+    if mixer.duck != target {
+        mixer.duck = if mixer.duck < target {
+            (mixer.duck + step).min(target)
+        } else {
+            (mixer.duck - step).max(target)
+        };
+    }
+}
+
+pub fn apply_mixer(
+    mixer: Res<AudioMixer>,
+    sinks_query: Query<(
+        AnyOf<(&AudioSink, &SpatialAudioSink)>,
+        &AudioBus,
+        &BaseVolume,
+    )>,
+) {
+    for ((sink, spatial_sink), bus, BaseVolume(base)) in &sinks_query {
+        let volume = base * mixer.gain(*bus);
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+/// Pause the sound effects playing in the game, leaving the music and the sounds of
+/// the pause menu alone.
+pub fn pause_sounds(
+    mut commands: Commands,
+    sinks_query: Query<(Entity, AnyOf<(&AudioSink, &SpatialAudioSink)>, &AudioBus)>,
+) {
+    for (entity, (sink, spatial_sink), bus) in &sinks_query {
+        if *bus != AudioBus::Sfx {
+            continue;
+        }
+        // Sounds already paused stay paused once the game resumes.
+        let playing = sink.is_some_and(|sink| !sink.is_paused())
+            || spatial_sink.is_some_and(|sink| !sink.is_paused());
+        if playing {
+            if let Some(sink) = sink {
+                sink.pause();
+            }
+            if let Some(sink) = spatial_sink {
+                sink.pause();
+            }
+            commands.entity(entity).insert(PausedWithGame);
+        }
+    }
+}
+
+pub fn resume_sounds(
+    mut commands: Commands,
+    sinks_query: Query<(Entity, AnyOf<(&AudioSink, &SpatialAudioSink)>), With<PausedWithGame>>,
+) {
+    for (entity, (sink, spatial_sink)) in &sinks_query {
+        if let Some(sink) = sink {
+            sink.play();
+        }
+        if let Some(sink) = spatial_sink {
+            sink.play();
+        }
+        commands.entity(entity).remove::<PausedWithGame>();
+    }
+}
+
+/// Load the sounds of the cues, synthesizing them when their files are missing.
+pub fn load_sound_cues(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut synth_sounds: ResMut<Assets<SynthSound>>,
+    config: Res<GameConfig>,
+) {
+    let cues = config.sound_cues.0.iter().map(|(kind, cue)| {
+        let missing = cue
+            .sounds
+            .iter()
+            .filter(|path| !storage::asset_path(path).exists())
+            .collect::<Vec<_>>();
+        let synthesize = cue.sounds.is_empty() || !missing.is_empty();
+        if !missing.is_empty() {
+            warn!("Missing {kind:?} sounds {missing:?}, synthesizing them instead");
+        }
+        let sounds = if synthesize {
+            cue.synth
+                .iter()
+                .map(|synth| CueSound::Synth(synth_sounds.add(SynthSound::new(&synth.params()))))
+                .collect()
+        } else {
+            cue.sounds
+                .iter()
+                .map(|path| CueSound::File(asset_server.load(path.clone())))
+                .collect()
+        };
+        let loaded = LoadedCue {
+            sounds,
+            volume: cue.volume,
+            next: 0,
+        };
+        (*kind, loaded)
+    });
+    commands.insert_resource(LoadedCues(cues.collect()));
+}
+
+pub fn play_sound_cues(
+    mut commands: Commands,
+    mut game_events: EventReader<GameEvent>,
+    mixer: Res<AudioMixer>,
+    mut cues: ResMut<LoadedCues>,
+) {
+    for event in game_events.read() {
+        let Some(cue) = cues.0.get_mut(&event.kind()) else {
+            continue;
+        };
+        match *event {
+            GameEvent::Shot { position }
+            | GameEvent::AlienKilled { position }
+            | GameEvent::PlayerKilled { position } => {
+                if let Some((sound, settings)) = cue.next_sound(PlaybackMode::Despawn) {
+                    sound.spawn(&mut commands, mixer.positional_sfx(settings, position));
+                }
+            }
+            // The loop of a mystery ship follows it as its child, replacing the previous one.
+            GameEvent::UfoSpawned { ufo } | GameEvent::UfoLeaving { ufo } => {
+                if commands.get_entity(ufo).is_none() {
+                    continue;
+                }
+                commands.entity(ufo).despawn_descendants();
+                if let Some((sound, settings)) = cue.next_sound(PlaybackMode::Loop) {
+                    let loop_sound =
+                        sound.spawn(&mut commands, mixer.positional_sfx(settings, Vec3::ZERO));
+                    commands.entity(ufo).add_child(loop_sound);
+                }
+            }
+            GameEvent::FormationStep | GameEvent::ButtonHovered | GameEvent::ButtonPressed => {
+                if let Some((sound, settings)) = cue.next_sound(PlaybackMode::Despawn) {
+                    sound.spawn(&mut commands, mixer.sfx(settings));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config::DEFAULT_CONFIG_PATH;
+use crate::game::waves::DEFAULT_WAVES_PATH;
+use crate::storage;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+pub const USAGE: &str =
+    "Usage: invaders [--config <path>] [--waves <path>] [--headless <games>] [--seed <seed>]\n                       [--record <path>] [--replay <path>]";
+
+#[derive(Debug)]
+pub struct Args {
+    pub config: PathBuf,
+    pub waves: PathBuf,
+    /// Number of games to simulate without a window, if any.
+    pub headless: Option<usize>,
+    /// Overrides the seed of the configuration.
+    pub seed: Option<u64>,
+    /// Where to write the replay of each game.
+    pub record: Option<PathBuf>,
+    /// Replay to play back instead of reading the keyboard.
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            config: PathBuf::from(DEFAULT_CONFIG_PATH),
+            waves: storage::asset_path(DEFAULT_WAVES_PATH),
+            headless: None,
+            seed: None,
+            record: None,
+            replay: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ArgsError {
+    MissingValue(String),
+    InvalidValue(String, String),
+    UnknownArgument(String),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingValue(flag) => write!(f, "missing value for `{flag}`"),
+            ArgsError::InvalidValue(flag, value) => {
+                write!(f, "invalid value `{value}` for `{flag}`")
+            }
+            ArgsError::UnknownArgument(arg) => write!(f, "unknown argument `{arg}`"),
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+impl Args {
+    /// Parse the command line arguments, without the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(ArgsError::MissingValue(arg.clone()));
+            match arg.as_str() {
+                "--config" => parsed.config = PathBuf::from(value()?),
+                "--waves" => parsed.waves = PathBuf::from(value()?),
+                "--headless" => {
+                    let games = value()?;
+                    let games = games
+                        .parse()
+                        .map_err(|_| ArgsError::InvalidValue(arg.clone(), games))?;
+                    parsed.headless = Some(games);
+                }
+                "--seed" => {
+                    let seed = value()?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| ArgsError::InvalidValue(arg.clone(), seed))?;
+                    parsed.seed = Some(seed);
+                }
+                "--record" => parsed.record = Some(PathBuf::from(value()?)),
+                "--replay" => parsed.replay = Some(PathBuf::from(value()?)),
+                _ => return Err(ArgsError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,455 @@
+use crate::audio::synth::{Synth, MAX_SYNTH_DURATION};
+use crate::audio::SoundCues;
+use crate::game::aliens::MarchMode;
+use crate::game::input::{GamepadBindings, KeyBindings};
+use crate::game::lasers::Projectile;
+use crate::settings::*;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::{fs, io};
+
+pub const DEFAULT_CONFIG_PATH: &str = "config.ron";
+
+/// Every gameplay and interface tunable, loaded from a RON file at startup.
+///
+/// Missing fields fall back to the values defined in `settings.rs`, so a
+/// config file only needs to list the values it overrides.
+#[derive(Resource, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Seed of the first game, the following ones use the next integers. A new
+    /// random seed is drawn for every game when it is not set.
+    pub seed: Option<u64>,
+
+    /// Keys bound to each action, e.g. `{ MoveLeft: [Left, A], Fire: [Space, Up] }`.
+    pub key_bindings: KeyBindings,
+    /// Gamepad buttons bound to each action, e.g. `{ Fire: [South, West] }`.
+    pub gamepad_bindings: GamepadBindings,
+    /// How far the left stick must be tilted before it counts, between 0 and 1.
+    pub gamepad_dead_zone: f32,
+    /// Sounds played for each kind of event, e.g.
+    /// `{ Shot: (sounds: ["audio/laser.ogg"], volume: 0.8) }`.
+    pub sound_cues: SoundCues,
+
+    pub player_size: Vec2,
+    pub player_speed: f32,
+
+    /// Fallback for the waves that do not set `shelters`, and for the single wave played
+    /// without a waves file.
+    pub num_shelters: usize,
+    pub shelter_size: Vec2,
+    /// Radius of the hole a laser blows in a shelter.
+    pub shelter_blast_radius: f32,
+    /// No longer used since shelters erode pixel by pixel, still read so that older
+    /// config files load.
+    #[serde(skip_serializing)]
+    pub initial_armor_value: Option<u32>,
+    /// No longer used, see `initial_armor_value`.
+    #[serde(skip_serializing)]
+    pub laser_damage: Option<u32>,
+
+    /// Widest line of a formation.
+    pub aliens_per_line: usize,
+    /// Lines of each type of alien in the single wave played without a waves file.
+    pub num_yellow: usize,
+    pub num_green: usize,
+    pub num_red: usize,
+    pub space_between_aliens: Vec2,
+    pub margin: f32,
+    pub alien_shoot_prob: f32,
+    /// Share of the alien shots fired from the column closest to the player.
+    pub targeted_shot_prob: f32,
+    pub alien_size: Vec2,
+    pub yellow_alien_value: u32,
+    pub green_alien_value: u32,
+    pub red_alien_value: u32,
+    pub yellow_projectile: Projectile,
+    pub green_projectile: Projectile,
+    pub red_projectile: Projectile,
+    /// Fallback for the waves that do not set `tick_duration`.
+    pub alien_tick_duration: f32,
+    pub march_mode: MarchMode,
+
+    /// Value of the mystery ship, indexed by the number of shots fired by the player.
+    pub ufo_score_table: Vec<u32>,
+    /// No longer used since the mystery ship is scored from `ufo_score_table`, still
+    /// read so that older config files load.
+    #[serde(skip_serializing)]
+    pub ufo_value: Option<u32>,
+    /// Fallback for the waves that do not set `ufo_spawn_prob`.
+    pub ufo_spawn_prob: f32,
+    pub ufo_size: Vec2,
+    pub ufo_speed: f32,
+
+    pub laser_size: Vec2,
+    pub player_laser_speed: f32,
+    pub alien_laser_speed: f32,
+    pub max_alien_lasers: usize,
+
+    pub explosion_duration: f32,
+    /// How long a shot alien shows as an explosion.
+    pub alien_explosion_duration: f32,
+    pub explosion_min_radius: f32,
+    pub explosion_max_radius: f32,
+
+    pub xp_gain_duration: f32,
+
+    pub floor_height: f32,
+    pub floor_thickness: f32,
+
+    pub background_color: Color,
+    pub scoreboard_font_size: f32,
+    pub text_color: Color,
+    pub menu_text_color: Color,
+    pub text_button_size: f32,
+    pub score_menu_text_size: f32,
+    pub button_width: f32,
+    pub button_height: f32,
+    pub button_margin: f32,
+    pub menu_title_size: f32,
+    pub normal_button: Color,
+    pub hovered_button: Color,
+    pub hovered_pressed_button: Color,
+    pub pressed_button: Color,
+    pub focus_ring_color: Color,
+    pub focus_ring_width: f32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            key_bindings: KeyBindings::default(),
+            gamepad_bindings: GamepadBindings::default(),
+            gamepad_dead_zone: GAMEPAD_DEAD_ZONE,
+            sound_cues: SoundCues::default(),
+            player_size: PLAYER_SIZE,
+            player_speed: PLAYER_SPEED,
+            num_shelters: NUM_SHELTERS,
+            shelter_size: SHELTER_SIZE,
+            shelter_blast_radius: SHELTER_BLAST_RADIUS,
+            initial_armor_value: None,
+            laser_damage: None,
+            aliens_per_line: ALIENS_PER_LINE,
+            num_yellow: NUM_YELLOW,
+            num_green: NUM_GREEN,
+            num_red: NUM_RED,
+            space_between_aliens: SPACE_BETWEEN_ALIENS,
+            margin: MARGIN,
+            alien_shoot_prob: ALIEN_SHOOT_PROB,
+            targeted_shot_prob: TARGETED_SHOT_PROB,
+            alien_size: ALIEN_SIZE,
+            yellow_alien_value: YELLOW_ALIEN_VALUE,
+            green_alien_value: GREEN_ALIEN_VALUE,
+            red_alien_value: RED_ALIEN_VALUE,
+            yellow_projectile: YELLOW_PROJECTILE,
+            green_projectile: GREEN_PROJECTILE,
+            red_projectile: RED_PROJECTILE,
+            alien_tick_duration: ALIEN_TICK_DURATION,
+            march_mode: MARCH_MODE,
+            ufo_score_table: UFO_SCORE_TABLE.to_vec(),
+            ufo_value: None,
+            ufo_spawn_prob: UFO_SPAWN_PROB,
+            ufo_size: UFO_SIZE,
+            ufo_speed: UFO_SPEED,
+            laser_size: LASER_SIZE,
+            player_laser_speed: PLAYER_LASER_SPEED,
+            alien_laser_speed: ALIEN_LASER_SPEED,
+            max_alien_lasers: MAX_ALIEN_LASERS,
+            explosion_duration: EXPLOSION_DURATION,
+            alien_explosion_duration: ALIEN_EXPLOSION_DURATION,
+            explosion_min_radius: EXPLOSION_MIN_RADIUS,
+            explosion_max_radius: EXPLOSION_MAX_RADIUS,
+            xp_gain_duration: XP_GAIN_DURATION,
+            floor_height: FLOOR_HEIGHT,
+            floor_thickness: FLOOR_THICKNESS,
+            background_color: BACKGROUND_COLOR,
+            scoreboard_font_size: SCOREBOARD_FONT_SIZE,
+            text_color: TEXT_COLOR,
+            menu_text_color: MENU_TEXT_COLOR,
+            text_button_size: TEXT_BUTTON_SIZE,
+            score_menu_text_size: SCORE_MENU_TEXT_SIZE,
+            button_width: BUTTON_WIDTH,
+            button_height: BUTTON_HEIGHT,
+            button_margin: BUTTON_MARGIN,
+            menu_title_size: MENU_TITLE_SIZE,
+            normal_button: NORMAL_BUTTON,
+            hovered_button: HOVERED_BUTTON,
+            hovered_pressed_button: HOVERED_PRESSED_BUTTON,
+            pressed_button: PRESSED_BUTTON,
+            focus_ring_color: FOCUS_RING_COLOR,
+            focus_ring_width: FOCUS_RING_WIDTH,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(Vec<String>),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read the file: {err}"),
+            ConfigError::Parse(err) => write!(f, "could not parse the file: {err}"),
+            ConfigError::Invalid(problems) => {
+                write!(f, "invalid values:")?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl GameConfig {
+    /// Load the configuration stored at `path`, or the defaults if there is no such file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::from_ron(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(ConfigError::Io(err)),
+        }
+    }
+
+    pub fn from_ron(contents: &str) -> Result<Self, ConfigError> {
+        let config: Self = ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(contents)
+            .map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Keys of the file that are read but no longer have any effect, along with the
+    /// reason why.
+    pub fn ignored_keys(&self) -> Vec<(&'static str, &'static str)> {
+        const SHELTERS_ERODE: &str = "shelters erode pixel by pixel";
+        let keys = [
+            (
+                "initial_armor_value",
+                self.initial_armor_value.is_some(),
+                SHELTERS_ERODE,
+            ),
+            ("laser_damage", self.laser_damage.is_some(), SHELTERS_ERODE),
+            (
+                "ufo_value",
+                self.ufo_value.is_some(),
+                "the mystery ship is scored from `ufo_score_table`",
+            ),
+        ];
+        keys.into_iter()
+            .filter_map(|(key, set, reason)| set.then_some((key, reason)))
+            .collect()
+    }
+
+    /// Check that the values can produce a playable game.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        let sizes = [
+            ("player_size", self.player_size),
+            ("shelter_size", self.shelter_size),
+            ("space_between_aliens", self.space_between_aliens),
+            ("alien_size", self.alien_size),
+            ("ufo_size", self.ufo_size),
+            ("laser_size", self.laser_size),
+        ];
+        for (name, size) in sizes {
+            if size.is_nan() || size.min_element() <= 0.0 {
+                problems.push(format!("`{name}` must be strictly positive, got {size}"));
+            }
+        }
+
+        let positives = [
+            ("player_speed", self.player_speed),
+            ("alien_tick_duration", self.alien_tick_duration),
+            ("ufo_speed", self.ufo_speed),
+            ("player_laser_speed", self.player_laser_speed),
+            ("alien_laser_speed", self.alien_laser_speed),
+            ("explosion_duration", self.explosion_duration),
+            ("alien_explosion_duration", self.alien_explosion_duration),
+            ("explosion_min_radius", self.explosion_min_radius),
+            ("shelter_blast_radius", self.shelter_blast_radius),
+            ("xp_gain_duration", self.xp_gain_duration),
+            ("floor_thickness", self.floor_thickness),
+        ];
+        for (name, value) in positives {
+            if value.is_nan() || value <= 0.0 {
+                problems.push(format!("`{name}` must be strictly positive, got {value}"));
+            }
+        }
+
+        let non_negatives = [
+            ("margin", self.margin),
+            ("floor_height", self.floor_height),
+            ("focus_ring_width", self.focus_ring_width),
+        ];
+        for (name, value) in non_negatives {
+            if value.is_nan() || value < 0.0 {
+                problems.push(format!("`{name}` must not be negative, got {value}"));
+            }
+        }
+
+        let probabilities = [
+            ("alien_shoot_prob", self.alien_shoot_prob),
+            ("targeted_shot_prob", self.targeted_shot_prob),
+            ("ufo_spawn_prob", self.ufo_spawn_prob),
+        ];
+        for (name, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("`{name}` must be between 0 and 1, got {value}"));
+            }
+        }
+
+        if self.explosion_max_radius < self.explosion_min_radius {
+            problems.push(format!(
+                "`explosion_max_radius` ({}) must not be smaller than `explosion_min_radius` ({})",
+                self.explosion_max_radius, self.explosion_min_radius
+            ));
+        }
+        if self.ufo_score_table.is_empty() {
+            problems.push("`ufo_score_table` must have at least one value".to_string());
+        }
+        if self.aliens_per_line == 0 {
+            problems.push("`aliens_per_line` must be at least 1".to_string());
+        }
+        if self.num_yellow + self.num_green + self.num_red == 0 {
+            problems.push("at least one line of aliens is required".to_string());
+        }
+
+        for (key, actions) in self.key_bindings.conflicts() {
+            problems.push(format!(
+                "`key_bindings`: {key:?} is bound to several actions: {actions:?}"
+            ));
+        }
+        for (button, actions) in self.gamepad_bindings.conflicts() {
+            problems.push(format!(
+                "`gamepad_bindings`: {button:?} is bound to several actions: {actions:?}"
+            ));
+        }
+        for (kind, cue) in &self.sound_cues.0 {
+            if cue.volume.is_nan() || cue.volume < 0.0 {
+                problems.push(format!(
+                    "`sound_cues`: the volume of {kind:?} must not be negative, got {}",
+                    cue.volume
+                ));
+            }
+            for params in cue.synth.iter().map(Synth::params) {
+                let checks = [
+                    (params.frequency > 0.0, "a positive frequency"),
+                    ((0.0..=1.0).contains(&params.duty), "a duty between 0 and 1"),
+                    (
+                        (0.0..=1.0).contains(&params.volume),
+                        "a volume between 0 and 1",
+                    ),
+                    (
+                        [params.attack, params.sustain, params.decay]
+                            .iter()
+                            .all(|duration| *duration >= 0.0),
+                        "envelope durations that are not negative",
+                    ),
+                ];
+                for (valid, expected) in checks {
+                    if !valid {
+                        problems.push(format!(
+                            "`sound_cues`: the synthesized sounds of {kind:?} need {expected}"
+                        ));
+                    }
+                }
+                if params.duration() > MAX_SYNTH_DURATION {
+                    problems.push(format!(
+                        "`sound_cues`: the synthesized sounds of {kind:?} must last at most \
+                         {MAX_SYNTH_DURATION} seconds, got {}",
+                        params.duration()
+                    ));
+                }
+            }
+        }
+        if !(0.0..1.0).contains(&self.gamepad_dead_zone) {
+            problems.push(format!(
+                "`gamepad_dead_zone` must be at least 0 and less than 1, got {}",
+                self.gamepad_dead_zone
+            ));
+        }
+
+        let resolution = self.window_resolution();
+        let shelters_width = self.num_shelters as f32 * self.shelter_size.x;
+        if shelters_width > resolution.x {
+            problems.push(format!(
+                "{} shelters of width {} do not fit in a {} pixels wide window",
+                self.num_shelters, self.shelter_size.x, resolution.x
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    pub fn window_resolution(&self) -> Vec2 {
+        let width = 2.0 * self.margin
+            + self.aliens_per_line as f32 * self.alien_size.x
+            + (self.aliens_per_line.max(1) - 1) as f32 * self.space_between_aliens.x;
+        let height = 600.0;
+        Vec2::new(width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_removed_shelter_keys() {
+        let config = GameConfig::from_ron("(initial_armor_value: 100, laser_damage: 5)").unwrap();
+        assert_eq!(
+            config.ignored_keys(),
+            [
+                ("initial_armor_value", "shelters erode pixel by pixel"),
+                ("laser_damage", "shelters erode pixel by pixel"),
+            ]
+        );
+        assert_eq!(
+            GameConfig {
+                initial_armor_value: None,
+                laser_damage: None,
+                ..config
+            },
+            GameConfig::default()
+        );
+    }
+
+    #[test]
+    fn accepts_removed_ufo_value() {
+        let config = GameConfig::from_ron("(ufo_value: 150)").unwrap();
+        assert_eq!(
+            config.ignored_keys(),
+            [(
+                "ufo_value",
+                "the mystery ship is scored from `ufo_score_table`"
+            )]
+        );
+        assert_eq!(
+            config.ufo_score_table,
+            GameConfig::default().ufo_score_table
+        );
+        assert!(!ron::to_string(&config).unwrap().contains("ufo_value"));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(GameConfig::from_ron("(armor: 100)").is_err());
+    }
+}
@@ -1,18 +1,25 @@
 pub mod systems;
 
 pub mod aliens;
+pub mod collision;
+pub mod input;
 pub mod lasers;
 pub mod player;
 pub mod shelters;
 pub mod transition;
+pub mod waves;
 
 use crate::game::aliens::AliensPlugin;
+use crate::game::collision::CollisionPlugin;
+use crate::game::input::{PlayerInputPlugin, TickSet};
 use crate::game::lasers::LasersPlugin;
 use crate::game::player::PlayerPlugin;
 use crate::game::shelters::SheltersPlugin;
-use crate::game::transition::TransitionPlugin;
+use crate::game::transition::{TransitionPlugin, TransitionState};
 use crate::{despawn_screen, AppState};
+use bevy::ecs::schedule::ExecutorKind;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use systems::*;
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
@@ -26,6 +33,58 @@ pub enum GameState {
 #[derive(Event)]
 pub struct GameOver;
 
+/// Something happening in the game or its menus that can be heard. The sounds of each
+/// kind of event come from the sound cues of the config.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum GameEvent {
+    Shot {
+        position: Vec3,
+    },
+    AlienKilled {
+        position: Vec3,
+    },
+    PlayerKilled {
+        position: Vec3,
+    },
+    UfoSpawned {
+        ufo: Entity,
+    },
+    /// The mystery ship started leaving the screen.
+    UfoLeaving {
+        ufo: Entity,
+    },
+    FormationStep,
+    ButtonHovered,
+    ButtonPressed,
+}
+
+impl GameEvent {
+    pub fn kind(&self) -> GameEventKind {
+        match self {
+            GameEvent::Shot { .. } => GameEventKind::Shot,
+            GameEvent::AlienKilled { .. } => GameEventKind::AlienKilled,
+            GameEvent::PlayerKilled { .. } => GameEventKind::PlayerKilled,
+            GameEvent::UfoSpawned { .. } => GameEventKind::UfoSpawned,
+            GameEvent::UfoLeaving { .. } => GameEventKind::UfoLeaving,
+            GameEvent::FormationStep => GameEventKind::FormationStep,
+            GameEvent::ButtonHovered => GameEventKind::ButtonHovered,
+            GameEvent::ButtonPressed => GameEventKind::ButtonPressed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+pub enum GameEventKind {
+    Shot,
+    AlienKilled,
+    PlayerKilled,
+    UfoSpawned,
+    UfoLeaving,
+    FormationStep,
+    ButtonHovered,
+    ButtonPressed,
+}
+
 #[derive(Component)]
 pub struct OnGameScreen;
 
@@ -52,17 +111,44 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        // A fixed update tick only depends on the previous one and the player input,
+        // so that a game can be replayed from its seed and recorded input.
+        app.edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
         app.add_systems(OnEnter(AppState::InGame), (reset_game_state, spawn_floor))
+            .add_plugins(PlayerInputPlugin)
             .add_plugins(PlayerPlugin)
             .add_plugins(AliensPlugin)
             .add_plugins(LasersPlugin)
+            .add_plugins(CollisionPlugin)
             .add_plugins(SheltersPlugin)
             .add_plugins(TransitionPlugin)
             .add_state::<GameState>()
             .add_event::<GameOver>()
+            .add_event::<GameEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_state_transition::<AppState>,
+                    apply_state_transition::<GameState>,
+                    apply_state_transition::<TransitionState>,
+                )
+                    .chain()
+                    .in_set(TickSet::ApplyStates),
+            )
+            .add_systems(
+                FixedUpdate,
+                toggle_pause
+                    .after(TickSet::Input)
+                    .before(TickSet::Simulation)
+                    .run_if(in_state(AppState::InGame)),
+            )
             .add_systems(
                 FixedUpdate,
                 handle_game_over
+                    .in_set(TickSet::Simulation)
                     .run_if(in_state(AppState::InGame))
                     .run_if(in_state(GameState::Running)),
             )
@@ -1,9 +1,14 @@
 pub mod systems;
 
+use crate::config::GameConfig;
+use crate::game::collision::CollisionSet;
+use crate::game::input::TickSet;
+use crate::game::lasers::Projectile;
+use crate::game::systems::reset_game_state;
 use crate::game::{EntityDirection, GameState};
-use crate::settings::{GREEN_ALIEN_VALUE, RED_ALIEN_VALUE, UFO_VALUE, YELLOW_ALIEN_VALUE};
 use crate::AppState;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use systems::*;
 
 #[derive(Event)]
@@ -16,7 +21,39 @@ pub struct AlienHit {
 #[derive(Component)]
 pub struct XpTimer(pub Timer);
 
-#[derive(Clone, Component)]
+/// Size of a frame in the sprite sheets of the aliens.
+pub const FRAME_SIZE: Vec2 = Vec2::new(40.0, 32.0);
+
+/// Size of a frame in the sprite sheet of the projectiles.
+pub const PROJECTILE_FRAME_SIZE: Vec2 = Vec2::new(3.0, 8.0);
+
+/// Sprite sheets of the aliens, holding the two frames they switch between every
+/// time they march.
+#[derive(Resource)]
+pub struct AlienSprites {
+    pub yellow: Handle<TextureAtlas>,
+    pub green: Handle<TextureAtlas>,
+    pub red: Handle<TextureAtlas>,
+    pub explosion: Handle<Image>,
+    /// One row of frames per kind of projectile.
+    pub projectiles: Handle<TextureAtlas>,
+}
+
+impl AlienSprites {
+    pub fn get(&self, alien: &Alien) -> Handle<TextureAtlas> {
+        match alien {
+            Alien::Yellow => self.yellow.clone(),
+            Alien::Green => self.green.clone(),
+            Alien::Red | Alien::Ufo => self.red.clone(),
+        }
+    }
+}
+
+/// What remains of a shot alien for a moment.
+#[derive(Component)]
+pub struct AlienExplosion(pub Timer);
+
+#[derive(Clone, Debug, PartialEq, Component)]
 pub enum Alien {
     Yellow,
     Green,
@@ -34,12 +71,26 @@ impl Alien {
         }
     }
 
-    pub fn value(&self) -> u32 {
+    /// What the alien shoots. Mystery ships never do, they are given the red one.
+    pub fn projectile(&self, config: &GameConfig) -> Projectile {
         match self {
-            Alien::Yellow => YELLOW_ALIEN_VALUE,
-            Alien::Green => GREEN_ALIEN_VALUE,
-            Alien::Red => RED_ALIEN_VALUE,
-            Alien::Ufo => UFO_VALUE,
+            Alien::Yellow => config.yellow_projectile,
+            Alien::Green => config.green_projectile,
+            Alien::Red | Alien::Ufo => config.red_projectile,
+        }
+    }
+
+    /// Points awarded for the alien, the value of a mystery ship depending on the number
+    /// of shots fired by the player, including the one that hit it.
+    pub fn value(&self, config: &GameConfig, shots_fired: u32) -> u32 {
+        match self {
+            Alien::Yellow => config.yellow_alien_value,
+            Alien::Green => config.green_alien_value,
+            Alien::Red => config.red_alien_value,
+            Alien::Ufo => {
+                let table = &config.ufo_score_table;
+                table[shots_fired as usize % table.len()]
+            }
         }
     }
 }
@@ -47,30 +98,108 @@ impl Alien {
 #[derive(Component)]
 pub struct Ufo(pub EntityDirection);
 
+/// Mystery ship past the edge of the screen it flies towards.
+#[derive(Component)]
+pub struct UfoLeaving;
+
+/// How the formation of aliens marches.
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum MarchMode {
+    /// Every alien moves at once when the alien timer fires.
+    #[default]
+    Formation,
+    /// One alien moves per fixed update tick, from the bottom left to the top right,
+    /// like the original arcade. The formation ripples and speeds up as aliens die.
+    Ripple,
+}
+
+/// Place of an alien in a ripple march.
+#[derive(Component)]
+pub struct MarchOrder(pub usize);
+
+/// Column of an alien in its formation. Only the lowest alien of a column shoots.
+#[derive(Component)]
+pub struct FormationColumn(pub usize);
+
+/// Progress of the current sweep of a ripple march through the aliens.
+#[derive(Resource, Default)]
+pub struct RippleMarch {
+    /// Place of the alien that moved last, none before the first one of the sweep.
+    pub last: Option<usize>,
+    /// Whether an alien reached a side of the play field during the sweep.
+    pub reached_side: bool,
+}
+
 pub struct AliensPlugin;
 
 impl Plugin for AliensPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<AlienHit>()
-            .add_systems(OnEnter(AppState::InGame), spawn_aliens)
+            .init_resource::<RippleMarch>()
+            .add_systems(Startup, load_alien_sprites)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                spawn_aliens.after(reset_game_state),
+            )
             .add_systems(
                 FixedUpdate,
-                (move_aliens, alien_reach_floor)
+                (move_aliens, ripple_march, alien_reach_floor)
                     .chain()
+                    .in_set(TickSet::Simulation)
+                    .before(CollisionSet)
                     .run_if(in_state(AppState::InGame))
                     .run_if(in_state(GameState::Running)),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
                 aliens_shoot
+                    .in_set(TickSet::Simulation)
                     .run_if(in_state(AppState::InGame))
                     .run_if(in_state(GameState::Running)),
             )
             .add_systems(
                 FixedUpdate,
-                (spawn_ufo, move_ufo, handle_alien_hit)
+                (spawn_ufo, move_ufo)
+                    .in_set(TickSet::Simulation)
+                    .before(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not(in_state(GameState::Pause))),
+            )
+            .add_systems(
+                FixedUpdate,
+                (handle_alien_hit, despawn_alien_explosions)
+                    .in_set(TickSet::Simulation)
+                    .after(CollisionSet)
                     .run_if(in_state(AppState::InGame))
                     .run_if(not(in_state(GameState::Pause))),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ufo_value_cycles_with_shots_fired() {
+        let config = GameConfig::default();
+        let top_scores: Vec<_> = (1..=60)
+            .filter(|shots| Alien::Ufo.value(&config, *shots) == 300)
+            .collect();
+        assert_eq!(top_scores, [8, 23, 38, 53]);
+        assert_eq!(Alien::Ufo.value(&config, 1), 50);
+        assert_eq!(Alien::Ufo.value(&config, 15), 100);
+    }
+
+    #[test]
+    fn ufo_value_follows_custom_table() {
+        let config = GameConfig {
+            ufo_score_table: vec![10, 20, 30],
+            ..default()
+        };
+        let values: Vec<_> = (0..6)
+            .map(|shots| Alien::Ufo.value(&config, shots))
+            .collect();
+        assert_eq!(values, [10, 20, 30, 10, 20, 30]);
+    }
+}
@@ -1,181 +1,366 @@
-use crate::game::aliens::{Alien, AlienHit, Ufo, XpTimer};
-use crate::game::lasers::Laser;
-use crate::game::{EntityDirection, GameOver, GameState, OnGameScreen};
-use crate::get_window_resolution;
+use crate::config::GameConfig;
+use crate::game::aliens::{
+    Alien, AlienExplosion, AlienHit, AlienSprites, FormationColumn, MarchMode, MarchOrder,
+    RippleMarch, Ufo, UfoLeaving, XpTimer, FRAME_SIZE, PROJECTILE_FRAME_SIZE,
+};
+use crate::game::collision::Collider;
+use crate::game::lasers::{Laser, Projectile, ProjectileAnimation, Zigzag, PROJECTILE_FRAMES};
+use crate::game::player::Player;
+use crate::game::waves::Waves;
+use crate::game::{EntityDirection, GameEvent, GameOver, GameState, OnGameScreen};
 use crate::resources::*;
-use crate::settings::*;
-use bevy::asset::{AssetServer, Handle};
-use bevy::audio::{AudioBundle, PlaybackMode, PlaybackSettings, Volume, VolumeLevel};
-use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use crate::user_settings::{Difficulty, UserSettings};
+use bevy::asset::AssetServer;
+use bevy::hierarchy::DespawnRecursiveExt;
 use bevy::math::Vec3;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
-use itertools::izip;
-use rand::random;
+use rand::Rng;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
-pub fn spawn_aliens(
+pub fn load_alien_sprites(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
-    alien_timer_duration: Res<AlienTimerDuration>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut sheet = |path| {
+        let atlas = TextureAtlas::from_grid(asset_server.load(path), FRAME_SIZE, 2, 1, None, None);
+        texture_atlases.add(atlas)
+    };
+    commands.insert_resource(AlienSprites {
+        yellow: sheet("sprites/yellow.png"),
+        green: sheet("sprites/green.png"),
+        red: sheet("sprites/red.png"),
+        explosion: asset_server.load("sprites/alien_explosion.png"),
+        projectiles: texture_atlases.add(TextureAtlas::from_grid(
+            asset_server.load("sprites/projectiles.png"),
+            PROJECTILE_FRAME_SIZE,
+            PROJECTILE_FRAMES,
+            3,
+            None,
+            None,
+        )),
+    });
+}
+
+pub fn spawn_aliens(
+    mut commands: Commands,
+    play_field: Res<PlayField>,
+    config: Res<GameConfig>,
+    waves: Res<Waves>,
+    current_wave: Res<CurrentWave>,
+    settings: Res<UserSettings>,
+    sprites: Res<AlienSprites>,
     mut alien_timer: ResMut<AlienTimer>,
+    mut march: ResMut<RippleMarch>,
 ) {
-    let window = window_query.single();
-
-    let sprites: [Handle<Image>; 3] = [
-        asset_server.load("sprites/yellow.png"),
-        asset_server.load("sprites/green.png"),
-        asset_server.load("sprites/red.png"),
-    ];
-    let lines = [NUM_YELLOW, NUM_GREEN, NUM_RED];
-    let alien_types = [Alien::Yellow, Alien::Green, Alien::Red];
-
-    let mut direction = Vec3::new(SPACE_BETWEEN_ALIENS.x + ALIEN_SIZE.x, 0.0, 0.0);
-    let mut translation = Vec3::new(MARGIN + ALIEN_SIZE.x / 2.0, window.height() - MARGIN, 0.0);
-
-    for (sprite, lines, alien_type) in izip!(sprites, lines, alien_types) {
-        for _ in 0..lines {
-            for j in 0..ALIENS_PER_LINE {
-                commands.spawn((
-                    SpriteBundle {
-                        texture: sprite.clone(),
-                        transform: Transform::from_translation(translation),
-                        ..default()
-                    },
-                    alien_type.clone(),
-                    OnGameScreen,
-                ));
-                if j != ALIENS_PER_LINE - 1 {
-                    translation += direction;
-                }
-            }
-            direction.x *= -1.0;
-            translation.y -= SPACE_BETWEEN_ALIENS.y + ALIEN_SIZE.y;
+    let wave = waves.get(current_wave.0);
+    let columns = wave
+        .formation
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or_default();
+    let rows = wave.formation.len();
+
+    let step = config.space_between_aliens + config.alien_size;
+    let origin = Vec3::new(
+        config.margin + config.alien_size.x / 2.0,
+        play_field.height() - wave.start_height,
+        0.0,
+    );
+
+    for (i, line) in wave.formation.iter().enumerate() {
+        for (j, cell) in line.iter().enumerate() {
+            let Some(alien_type) = cell else {
+                continue;
+            };
+            let translation = origin + Vec3::new(j as f32 * step.x, -(i as f32) * step.y, 0.0);
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: sprites.get(alien_type),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                alien_type.clone(),
+                // The ripple march starts from the bottom left alien.
+                MarchOrder((rows - 1 - i) * columns + j),
+                FormationColumn(j),
+                Collider::new(config.alien_size),
+                OnGameScreen,
+            ));
         }
     }
 
+    *march = RippleMarch::default();
+
     // Reset the timer.
-    alien_timer.set_duration(alien_timer_duration.0);
+    let tick_duration = waves.tick_duration(current_wave.0) * settings.difficulty.tick_factor();
+    alien_timer.set_duration(Duration::from_secs_f32(tick_duration));
+}
+
+/// Distance an alien covers in one step towards `direction`.
+fn march_step(config: &GameConfig, direction: &EntityDirection) -> Vec3 {
+    Vec3::new(config.alien_size.x / 4.0, config.alien_size.y / 2.0, 0.0) * direction.mask()
+}
+
+fn reached_side(transform: &Transform, config: &GameConfig, play_field: &PlayField) -> bool {
+    let half_alien_width = config.alien_size.x / 2.0;
+    let x = transform.translation.x;
+    x <= half_alien_width || x >= play_field.width() - half_alien_width
+}
+
+/// Pick the direction of the next step once every alien made the current one.
+fn turn(alien_direction: &mut AlienDirection, reached_side: bool) {
+    if let EntityDirection::Down = alien_direction.next {
+        // If aliens were moving down we change their direction before the next call.
+        alien_direction.next = match alien_direction.previous {
+            EntityDirection::Left => EntityDirection::Right,
+            EntityDirection::Right => EntityDirection::Left,
+            _ => panic!("Previous alien direction should be either left or right."),
+        };
+        alien_direction.previous = alien_direction.next.clone();
+    } else if reached_side {
+        alien_direction.next = EntityDirection::Down;
+    }
 }
 
 pub fn move_aliens(
-    mut commands: Commands,
-    mut aliens_query: Query<&mut Transform, (With<Alien>, Without<Laser>, Without<Ufo>)>,
+    mut aliens_query: Query<
+        (&mut Transform, &mut TextureAtlasSprite),
+        (With<Alien>, Without<Laser>, Without<Ufo>),
+    >,
+    config: Res<GameConfig>,
+    play_field: Res<PlayField>,
     time: Res<Time>,
+    mut game_events: EventWriter<GameEvent>,
     mut alien_direction: ResMut<AlienDirection>,
-    mut sounds: ResMut<AlienSounds>,
     mut timer: ResMut<AlienTimer>,
 ) {
+    if config.march_mode != MarchMode::Formation {
+        return;
+    }
     if timer.tick(time.delta()).just_finished() {
-        let mut translation = Vec3::new(ALIEN_SIZE.x / 4.0, ALIEN_SIZE.y / 2.0, 0.0);
-        let next = alien_direction.next.clone();
-        translation *= next.mask();
+        let translation = march_step(&config, &alien_direction.next);
 
-        // Move each alien.
-        aliens_query.iter_mut().for_each(|mut transform| {
-            transform.translation += translation;
-        });
-
-        // Play the sound of the aliens moving, if any.
-        if aliens_query.iter().count() > 0 {
-            commands.spawn(AudioBundle {
-                source: sounds.get(),
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: Volume::Relative(VolumeLevel::new(0.7)),
-                    ..default()
-                },
+        // Move each alien, switching to its other frame.
+        aliens_query
+            .iter_mut()
+            .for_each(|(mut transform, mut sprite)| {
+                transform.translation += translation;
+                sprite.index = 1 - sprite.index;
             });
+
+        if !aliens_query.is_empty() {
+            game_events.send(GameEvent::FormationStep);
         }
 
-        if let EntityDirection::Down = alien_direction.next {
-            // If aliens were moving down we change their direction before the next call.
-            alien_direction.next = match alien_direction.previous {
-                EntityDirection::Left => EntityDirection::Right,
-                EntityDirection::Right => EntityDirection::Left,
-                _ => panic!("Previous alien direction should be either left or right."),
+        let moving_down = alien_direction.next == EntityDirection::Down;
+        let reached_side = aliens_query
+            .iter()
+            .any(|(transform, _)| reached_side(transform, &config, &play_field));
+        turn(&mut alien_direction, reached_side);
+
+        if !moving_down && reached_side {
+            // Decrease the duration of the timer to make aliens move faster.
+            let current_tick = timer.duration().as_secs_f32();
+            timer.set_duration(Duration::from_secs_f32(current_tick / 1.1));
+        }
+    }
+}
+
+pub fn ripple_march(
+    mut aliens_query: Query<
+        (&mut Transform, &mut TextureAtlasSprite, &MarchOrder),
+        (With<Alien>, Without<Laser>, Without<Ufo>),
+    >,
+    config: Res<GameConfig>,
+    play_field: Res<PlayField>,
+    mut alien_direction: ResMut<AlienDirection>,
+    mut game_events: EventWriter<GameEvent>,
+    mut march: ResMut<RippleMarch>,
+) {
+    if config.march_mode != MarchMode::Ripple {
+        return;
+    }
+
+    let orders: Vec<usize> = aliens_query.iter().map(|(_, _, order)| order.0).collect();
+    let next_after = |last: Option<usize>| {
+        orders
+            .iter()
+            .copied()
+            .filter(|order| last.is_none_or(|last| *order > last))
+            .min()
+    };
+    let next = match next_after(march.last) {
+        Some(next) => next,
+        // Every alien made its step, start the next sweep.
+        None if march.last.is_some() => {
+            turn(&mut alien_direction, march.reached_side);
+            *march = RippleMarch::default();
+            let Some(next) = next_after(None) else {
+                return;
             };
-            alien_direction.previous = alien_direction.next.clone();
-        } else {
-            // Check if an alien hit a side.
-            let resolution = get_window_resolution();
-            let half_alien_width = ALIEN_SIZE.x / 2.0;
-
-            if aliens_query.iter().any(|transform| {
-                let x = transform.translation.x;
-                x <= half_alien_width || x >= resolution.x - half_alien_width
-            }) {
-                alien_direction.next = EntityDirection::Down;
-
-                // Decrease the duration of the timer to make aliens move faster.
-                let current_tick = timer.duration().as_secs_f32();
-                timer.set_duration(Duration::from_secs_f32(current_tick / 1.1));
-            }
+            next
+        }
+        None => return,
+    };
+    if march.last.is_none() {
+        game_events.send(GameEvent::FormationStep);
+    }
+
+    let translation = march_step(&config, &alien_direction.next);
+    for (mut transform, mut sprite, order) in &mut aliens_query {
+        if order.0 == next {
+            transform.translation += translation;
+            sprite.index = 1 - sprite.index;
+            march.reached_side |= reached_side(&transform, &config, &play_field);
         }
     }
+    march.last = Some(next);
+}
+
+/// The lowest alien of each column, the only ones in position to shoot, from left to right.
+fn shooters<'a>(
+    aliens: impl IntoIterator<Item = (Entity, &'a Transform, &'a Alien, &'a FormationColumn)>,
+) -> Vec<(Entity, &'a Transform, &'a Alien)> {
+    let mut lowest = BTreeMap::new();
+    for (entity, transform, alien, column) in aliens {
+        lowest
+            .entry(column.0)
+            .and_modify(|shooter: &mut (Entity, &Transform, &Alien)| {
+                if transform.translation.y < shooter.1.translation.y {
+                    *shooter = (entity, transform, alien);
+                }
+            })
+            .or_insert((entity, transform, alien));
+    }
+    lowest.into_values().collect()
+}
+
+/// Index of the shooter whose column is the closest to the player.
+fn targeted_shooter(shooters: &[(Entity, &Transform, &Alien)], player_x: f32) -> Option<usize> {
+    shooters
+        .iter()
+        .map(|(_, transform, _)| (transform.translation.x - player_x).abs())
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Probability that the formation fires during a tick, with `alive` of its `total` aliens
+/// left. It fires as often as all of its aliens would, one shot at most per tick.
+fn formation_fire_prob(
+    config: &GameConfig,
+    difficulty: Difficulty,
+    alive: usize,
+    total: usize,
+) -> f32 {
+    let fire_factor = difficulty.fire_factor(alive as f32 / total as f32);
+    config.alien_shoot_prob * alive as f32 * fire_factor
 }
 
 pub fn aliens_shoot(
     mut commands: Commands,
-    aliens_query: Query<(Entity, &Transform, &Alien), Without<Ufo>>,
+    aliens_query: Query<(Entity, &Transform, &Alien, &FormationColumn)>,
     lasers_query: Query<&Laser, With<Alien>>,
+    player_query: Query<&Transform, (With<Player>, Without<Laser>)>,
+    sprites: Res<AlienSprites>,
+    config: Res<GameConfig>,
+    waves: Res<Waves>,
+    current_wave: Res<CurrentWave>,
+    settings: Res<UserSettings>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut laser_count = lasers_query.iter().count();
-    let total_aliens = ALIENS_PER_LINE * (NUM_GREEN + NUM_RED + NUM_YELLOW);
-    let scaling_factor = aliens_query.iter().count() as f32 / total_aliens as f32;
+    if lasers_query.iter().count() >= config.max_alien_lasers {
+        return;
+    }
 
-    for (alien_entity, alien_transform, alien_type) in aliens_query.iter() {
-        if laser_count == MAX_ALIEN_LASERS
-            || lasers_query
+    let alive = aliens_query.iter().count();
+    let total_aliens = waves.get(current_wave.0).alien_count();
+    let fire_prob = formation_fire_prob(&config, settings.difficulty, alive, total_aliens);
+    if rng.gen::<f32>() >= fire_prob {
+        return;
+    }
+
+    // An alien does not shoot again while its previous shot is in flight.
+    let shooters: Vec<_> = shooters(aliens_query.iter())
+        .into_iter()
+        .filter(|(entity, ..)| {
+            !lasers_query
                 .iter()
-                .filter_map(|&Laser { source, .. }| source)
-                .any(|source_entity| source_entity == alien_entity)
-        {
-            break;
-        }
+                .any(|laser| laser.source == Some(*entity))
+        })
+        .collect();
+    if shooters.is_empty() {
+        return;
+    }
 
-        if random::<f32>() * scaling_factor < ALIEN_SHOOT_PROB {
-            let translation = alien_transform.translation;
-            let half_alien_height = ALIEN_SIZE.y / 2.0;
+    let player_transform = player_query.get_single().ok();
+    let targeted = player_transform
+        .and_then(|transform| targeted_shooter(&shooters, transform.translation.x))
+        .filter(|_| rng.gen::<f32>() < config.targeted_shot_prob);
+    let index = targeted.unwrap_or_else(|| rng.gen_range(0..shooters.len()));
+    let (alien_entity, alien_transform, alien_type) = shooters[index];
 
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: alien_type.color(),
-                        ..default()
-                    },
-                    transform: Transform {
-                        translation: Vec3::new(
-                            translation.x,
-                            translation.y - half_alien_height,
-                            0.0,
-                        ),
-                        scale: LASER_SIZE.extend(0.0),
-                        ..default()
-                    },
-                    ..default()
-                },
-                Laser {
-                    direction: EntityDirection::Down,
-                    speed: ALIEN_LASER_SPEED,
-                    source: Some(alien_entity),
-                },
-                alien_type.clone(),
-                OnGameScreen,
-            ));
+    let translation = alien_transform.translation;
+    let half_alien_height = config.alien_size.y / 2.0;
+    let origin = Vec3::new(translation.x, translation.y - half_alien_height, 0.0);
+    let projectile = alien_type.projectile(&config);
+    let speed = projectile.speed(&config);
 
-            laser_count += 1;
+    let direction = match (projectile, player_transform) {
+        // Aim at the player, but never up nor sideways.
+        (Projectile::Plunger, Some(player_transform)) => {
+            let to_player = (player_transform.translation - origin).truncate();
+            Vec2::new(to_player.x, to_player.y.min(-to_player.x.abs()))
+                .try_normalize()
+                .unwrap_or(Vec2::NEG_Y)
         }
+        _ => Vec2::NEG_Y,
+    };
+    let animation = ProjectileAnimation::new(projectile);
+
+    let mut laser = commands.spawn((
+        SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                index: animation.first_frame,
+                color: alien_type.color(),
+                custom_size: Some(config.laser_size),
+                ..default()
+            },
+            texture_atlas: sprites.projectiles.clone(),
+            transform: Transform::from_translation(origin)
+                .with_rotation(Quat::from_rotation_arc_2d(Vec2::NEG_Y, direction)),
+            ..default()
+        },
+        Laser {
+            velocity: direction * speed,
+            source: Some(alien_entity),
+        },
+        animation,
+        Collider::new(config.laser_size),
+        alien_type.clone(),
+        OnGameScreen,
+    ));
+    if projectile == Projectile::Squiggly {
+        laser.insert(Zigzag {
+            amplitude: config.laser_size.x,
+            frequency: 2.0,
+            elapsed: 0.0,
+        });
     }
 }
 
 pub fn alien_reach_floor(
     mut game_over_event_writer: EventWriter<GameOver>,
     aliens_query: Query<&Transform, (With<Alien>, Without<Laser>)>,
+    config: Res<GameConfig>,
 ) {
     for alien_transform in aliens_query.iter() {
-        if alien_transform.translation.y - ALIEN_SIZE.y / 2.0 < FLOOR_HEIGHT + FLOOR_THICKNESS {
+        if alien_transform.translation.y - config.alien_size.y / 2.0
+            < config.floor_height + config.floor_thickness
+        {
             game_over_event_writer.send(GameOver);
         }
     }
@@ -186,10 +371,12 @@ pub fn handle_alien_hit(
     mut alien_hit_event_reader: EventReader<AlienHit>,
     aliens_query: Query<&Alien, (Without<Laser>, Without<Ufo>)>,
     asset_server: Res<AssetServer>,
-    invader_killed_sound: Res<InvaderKilledSound>,
+    config: Res<GameConfig>,
+    sprites: Res<AlienSprites>,
+    mut game_events: EventWriter<GameEvent>,
     mut alien_timer: ResMut<AlienTimer>,
-    mut alien_timer_duration: ResMut<AlienTimerDuration>,
     mut lives_remaining: ResMut<LivesRemaining>,
+    shots_fired: Res<ShotsFired>,
     mut score: ResMut<PlayerScore>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
@@ -204,17 +391,39 @@ pub fn handle_alien_hit(
         if let Some(entity_commands) = commands.get_entity(*id) {
             entity_commands.despawn_recursive();
 
-            // Play an explosion sound when an alien dies.
-            commands.spawn(AudioBundle {
-                source: invader_killed_sound.0.clone(),
-                settings: PlaybackSettings::DESPAWN,
+            // Leave an explosion where the alien was.
+            let size = match alien_type {
+                Alien::Ufo => config.ufo_size,
+                _ => FRAME_SIZE,
+            };
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: alien_type.color(),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    texture: sprites.explosion.clone(),
+                    transform: Transform::from_translation(position.extend(0.0)),
+                    ..default()
+                },
+                AlienExplosion(Timer::from_seconds(
+                    config.alien_explosion_duration,
+                    TimerMode::Once,
+                )),
+                OnGameScreen,
+            ));
+
+            game_events.send(GameEvent::AlienKilled {
+                position: position.extend(0.0),
             });
 
             // Increase the player score.
-            score.0 += alien_type.value();
+            let value = alien_type.value(&config, shots_fired.0);
+            score.0 += value;
 
             // Show the alien value.
-            let text = format!("+{}XP", alien_type.value());
+            let text = format!("+{}XP", value);
             let font = asset_server.load("fonts/font.ttf");
             commands.spawn((
                 TextBundle::from_section(
@@ -232,7 +441,10 @@ pub fn handle_alien_hit(
                     bottom: Val::Px(position.y),
                     ..default()
                 }),
-                XpTimer(Timer::from_seconds(XP_GAIN_DURATION, TimerMode::Once)),
+                XpTimer(Timer::from_seconds(
+                    config.xp_gain_duration,
+                    TimerMode::Once,
+                )),
                 OnGameScreen,
             ));
 
@@ -241,9 +453,6 @@ pub fn handle_alien_hit(
                 next_game_state.set(GameState::Transition);
                 if lives_remaining.0 < 5 {
                     lives_remaining.0 += 1;
-                    // 10% speed increase.
-                    let duration = alien_timer_duration.0.as_secs_f32() * 0.90;
-                    alien_timer_duration.0 = Duration::from_secs_f32(duration);
                 }
             } else if aliens_remaining < 25 {
                 // If there are less than 25 aliens remaining, increase their speed
@@ -256,12 +465,29 @@ pub fn handle_alien_hit(
     }
 }
 
+pub fn despawn_alien_explosions(
+    mut commands: Commands,
+    mut explosions_query: Query<(Entity, &mut AlienExplosion)>,
+    time: Res<Time>,
+) {
+    for (entity, mut explosion) in &mut explosions_query {
+        if explosion.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 pub fn spawn_ufo(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    play_field: Res<PlayField>,
     ufo_query: Query<&Ufo>,
     asset_server: Res<AssetServer>,
+    mut game_events: EventWriter<GameEvent>,
+    config: Res<GameConfig>,
+    waves: Res<Waves>,
+    current_wave: Res<CurrentWave>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
     mut ufo_timer: ResMut<UfoTimer>,
 ) {
     if ufo_query.get_single().is_ok() {
@@ -271,23 +497,22 @@ pub fn spawn_ufo(
 
     if ufo_timer.tick(time.delta()).just_finished() {
         // Spawn a mystery ship.
-        if random::<f32>() < UFO_SPAWN_PROB {
-            let window = window_query.single();
-
-            let y = window.height() - UFO_SIZE.y;
-            let (direction, spawn_position) = if random() {
+        if rng.gen::<f32>() < waves.get(current_wave.0).ufo_spawn_prob {
+            let ufo_size = config.ufo_size;
+            let y = play_field.height() - ufo_size.y;
+            let (direction, spawn_position) = if rng.gen() {
                 let dir = EntityDirection::Left;
                 // Spawn at the right edge of the window (with a little margin).
-                let spawn = Vec3::new(window.width() + UFO_SIZE.x, y, 0.0);
+                let spawn = Vec3::new(play_field.width() + ufo_size.x, y, 0.0);
                 (dir, spawn)
             } else {
                 let dir = EntityDirection::Right;
                 // Spawn at the left edge of the window.
-                let spawn = Vec3::new(-UFO_SIZE.x, y, 0.0);
+                let spawn = Vec3::new(-ufo_size.x, y, 0.0);
                 (dir, spawn)
             };
 
-            commands
+            let ufo = commands
                 .spawn((
                     SpriteBundle {
                         texture: asset_server.load("sprites/ufo.png"),
@@ -299,38 +524,331 @@ pub fn spawn_ufo(
                     },
                     Ufo(direction),
                     Alien::Ufo,
+                    Collider::new(ufo_size),
                     OnGameScreen,
                 ))
-                .with_children(|parent| {
-                    parent.spawn(AudioBundle {
-                        source: asset_server.load("audio/ufo_highpitch.ogg"),
-                        settings: PlaybackSettings {
-                            mode: PlaybackMode::Loop,
-                            volume: Volume::Relative(VolumeLevel::new(0.6)),
-                            ..default()
-                        },
-                    });
-                });
+                .id();
+            game_events.send(GameEvent::UfoSpawned { ufo });
         }
     }
 }
 
 pub fn move_ufo(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    mut ufo_query: Query<(Entity, &mut Transform, &Ufo)>,
+    play_field: Res<PlayField>,
+    mut ufo_query: Query<(Entity, &mut Transform, &Ufo, Has<UfoLeaving>)>,
+    mut game_events: EventWriter<GameEvent>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
-    if let Ok((ufo_entity, mut transform, Ufo(direction))) = ufo_query.get_single_mut() {
-        let window = window_query.single();
-
-        transform.translation += direction.mask() * UFO_SPEED * time.delta_seconds();
+    if let Ok((ufo_entity, mut transform, Ufo(direction), leaving)) = ufo_query.get_single_mut() {
+        transform.translation += direction.mask() * config.ufo_speed * time.delta_seconds();
 
         let x = transform.translation.x;
         // Add a little margin, so it does not get despawn immediately.
         let margin = 10.0;
-        if x >= window.width() + UFO_SIZE.x + margin || x <= -(UFO_SIZE.x + margin) {
+        let ufo_width = config.ufo_size.x;
+        if x >= play_field.width() + ufo_width + margin || x <= -(ufo_width + margin) {
             commands.entity(ufo_entity).despawn_recursive();
+            return;
+        }
+
+        // The ship sounds different once it starts leaving the screen.
+        let half_width = ufo_width / 2.0;
+        let past_edge = match direction {
+            EntityDirection::Left => x - half_width < 0.0,
+            _ => x + half_width > play_field.width(),
+        };
+        if past_edge && !leaving {
+            commands.entity(ufo_entity).insert(UfoLeaving);
+            game_events.send(GameEvent::UfoLeaving { ufo: ufo_entity });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::waves::Wave;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::HashMap;
+
+    const COLUMNS: usize = 5;
+    const ROWS: usize = 3;
+    const SPACING: f32 = 50.0;
+
+    /// A headless world holding a full formation of `COLUMNS` by `ROWS` aliens, the player
+    /// standing under `player_column`.
+    fn formation(config: GameConfig, player_column: usize) -> World {
+        let mut world = World::new();
+        world.insert_resource(Waves(vec![Wave::from_config(&config)]));
+        world.insert_resource(config);
+        world.insert_resource(CurrentWave(0));
+        world.insert_resource(UserSettings::default());
+        world.insert_resource(GameRng::new(Some(7)));
+        world.insert_resource(AlienSprites {
+            yellow: default(),
+            green: default(),
+            red: default(),
+            explosion: default(),
+            projectiles: default(),
+        });
+        for column in 0..COLUMNS {
+            for row in 0..ROWS {
+                let translation =
+                    Vec3::new(column as f32 * SPACING, 500.0 - row as f32 * SPACING, 0.0);
+                world.spawn((
+                    Transform::from_translation(translation),
+                    Alien::Green,
+                    FormationColumn(column),
+                ));
+            }
+        }
+        world.spawn((
+            Transform::from_xyz(player_column as f32 * SPACING + 10.0, 50.0, 0.0),
+            Player,
+        ));
+        world
+    }
+
+    /// Let the formation shoot once, returning the column and the height of the shooter.
+    fn shoot(world: &mut World) -> (usize, f32) {
+        world.run_system_once(aliens_shoot);
+        let shots: Vec<_> = world
+            .query::<(Entity, &Laser)>()
+            .iter(world)
+            .map(|(entity, laser)| (entity, laser.source.unwrap()))
+            .collect();
+        assert_eq!(shots.len(), 1);
+        let (laser, shooter) = shots[0];
+        world.despawn(laser);
+        let shooter = world.entity(shooter);
+        (
+            shooter.get::<FormationColumn>().unwrap().0,
+            shooter.get::<Transform>().unwrap().translation.y,
+        )
+    }
+
+    fn always_firing(targeted_shot_prob: f32) -> GameConfig {
+        GameConfig {
+            alien_shoot_prob: 1.0,
+            targeted_shot_prob,
+            ..default()
         }
     }
+
+    #[test]
+    fn only_the_bottom_of_each_column_fires() {
+        let mut world = formation(always_firing(0.0), 0);
+        let bottom = 500.0 - (ROWS - 1) as f32 * SPACING;
+        let mut columns = HashMap::new();
+        for _ in 0..200 {
+            let (column, height) = shoot(&mut world);
+            assert_eq!(height, bottom);
+            *columns.entry(column).or_insert(0) += 1;
+        }
+        // Untargeted shots come from every column.
+        assert_eq!(columns.len(), COLUMNS);
+    }
+
+    #[test]
+    fn a_column_fires_from_its_next_alien_once_the_bottom_one_is_gone() {
+        let mut world = formation(always_firing(1.0), 3);
+        let (column, height) = shoot(&mut world);
+        assert_eq!(column, 3);
+
+        let bottom = world
+            .query::<(Entity, &Transform, &FormationColumn)>()
+            .iter(&world)
+            .find(|(_, transform, formation_column)| {
+                formation_column.0 == column && transform.translation.y == height
+            })
+            .map(|(entity, ..)| entity)
+            .unwrap();
+        world.despawn(bottom);
+
+        assert_eq!(shoot(&mut world), (3, height + SPACING));
+    }
+
+    #[test]
+    fn targeted_shots_come_from_above_the_player() {
+        let mut world = formation(always_firing(1.0), 1);
+        for _ in 0..20 {
+            assert_eq!(shoot(&mut world).0, 1);
+        }
+    }
+
+    #[test]
+    fn the_column_above_the_player_is_preferred() {
+        let mut world = formation(always_firing(GameConfig::default().targeted_shot_prob), 4);
+        let mut counts = [0; COLUMNS];
+        for _ in 0..500 {
+            counts[shoot(&mut world).0] += 1;
+        }
+        let others = counts[..4].iter().max().unwrap();
+        assert!(counts[4] > 2 * others, "{counts:?}");
+    }
+
+    #[test]
+    fn the_shooter_waits_for_its_shot_to_land() {
+        let mut world = formation(always_firing(1.0), 2);
+        world.run_system_once(aliens_shoot);
+        world.run_system_once(aliens_shoot);
+        let shooters: Vec<_> = world
+            .query::<&Laser>()
+            .iter(&world)
+            .map(|laser| laser.source.unwrap())
+            .collect();
+        assert_eq!(shooters.len(), 2);
+        assert_ne!(shooters[0], shooters[1]);
+    }
+
+    #[test]
+    fn fire_rate_as_aliens_die() {
+        let config = GameConfig::default();
+        let total = 55;
+        let rate = |difficulty, alive| formation_fire_prob(&config, difficulty, alive, total);
+
+        // On normal the formation keeps firing at the same rate as it loses aliens, until
+        // the last few.
+        let full = rate(Difficulty::Normal, total);
+        for alive in [30, 10, 3] {
+            assert!((rate(Difficulty::Normal, alive) / full - 1.0).abs() < 1e-4);
+        }
+        assert!(rate(Difficulty::Normal, 1) < full);
+        // It slows down on easy and speeds up on hard.
+        for (more, fewer) in [(55, 30), (30, 10), (10, 3)] {
+            assert!(rate(Difficulty::Easy, more) > rate(Difficulty::Easy, fewer));
+            assert!(rate(Difficulty::Hard, more) < rate(Difficulty::Hard, fewer));
+        }
+        // A full formation fires less on easy and more on hard.
+        assert!(rate(Difficulty::Easy, total) < rate(Difficulty::Normal, total));
+        assert!(rate(Difficulty::Hard, total) > rate(Difficulty::Normal, total));
+        // The last aliens on hard do not fire without bound.
+        assert!(rate(Difficulty::Hard, 1).is_finite());
+        assert_eq!(rate(Difficulty::Hard, 0), 0.0);
+    }
+
+    /// A headless world holding the aliens of `formation`, spawned as in a game that
+    /// marches them one at a time, first towards `direction`.
+    fn ripple_formation(formation: &str, direction: EntityDirection) -> World {
+        let config = GameConfig {
+            march_mode: MarchMode::Ripple,
+            ..default()
+        };
+        let mut world = World::new();
+        let waves = Waves::from_ron(&format!("[(formation: {formation})]"), &config).unwrap();
+        world.insert_resource(PlayField(config.window_resolution()));
+        world.insert_resource(waves);
+        world.insert_resource(config);
+        world.insert_resource(CurrentWave(0));
+        world.insert_resource(UserSettings::default());
+        world.insert_resource(AlienTimer(Timer::from_seconds(1.0, TimerMode::Repeating)));
+        world.insert_resource(AlienDirection {
+            previous: direction.clone(),
+            next: direction,
+        });
+        world.init_resource::<RippleMarch>();
+        world.init_resource::<Events<GameEvent>>();
+        world.insert_resource(AlienSprites {
+            yellow: default(),
+            green: default(),
+            red: default(),
+            explosion: default(),
+            projectiles: default(),
+        });
+        world.run_system_once(spawn_aliens);
+        world
+    }
+
+    fn alien_positions(world: &mut World) -> HashMap<Entity, Vec3> {
+        world
+            .query_filtered::<(Entity, &Transform), With<Alien>>()
+            .iter(world)
+            .map(|(entity, transform)| (entity, transform.translation))
+            .collect()
+    }
+
+    /// Run one tick of the ripple march, returning the alien that moved, where it stood
+    /// and the step it made.
+    fn ripple_step(world: &mut World) -> (Entity, Vec3, Vec3) {
+        let before = alien_positions(world);
+        world.run_system_once(ripple_march);
+        let moved: Vec<_> = alien_positions(world)
+            .into_iter()
+            .filter(|(entity, position)| before[entity] != *position)
+            .map(|(entity, position)| (entity, before[&entity], position - before[&entity]))
+            .collect();
+        assert_eq!(moved.len(), 1, "{moved:?}");
+        moved[0]
+    }
+
+    fn formation_steps(world: &World) -> usize {
+        let events = world.resource::<Events<GameEvent>>();
+        events
+            .get_reader()
+            .read(events)
+            .filter(|event| matches!(event, GameEvent::FormationStep))
+            .count()
+    }
+
+    #[test]
+    fn ripple_sweeps_from_bottom_left_to_top_right() {
+        let mut world = ripple_formation(r#"["GGG", "RRR"]"#, EntityDirection::Right);
+        let mut sweep: Vec<_> = (0..6).map(|_| ripple_step(&mut world)).collect();
+        let order: Vec<_> = sweep.iter().map(|(entity, _, _)| *entity).collect();
+
+        sweep.sort_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+        let expected: Vec<_> = sweep.iter().map(|(entity, _, _)| *entity).collect();
+        assert_eq!(order, expected);
+        assert!(sweep
+            .iter()
+            .all(|(_, _, step)| step.x > 0.0 && step.y == 0.0));
+        // The formation steps once per sweep, not once per alien.
+        assert_eq!(formation_steps(&world), 1);
+
+        // The next sweep starts over from the bottom left alien.
+        assert_eq!(ripple_step(&mut world).0, order[0]);
+        assert_eq!(formation_steps(&world), 2);
+    }
+
+    #[test]
+    fn ripple_turns_only_after_the_sweep_reaching_a_side() {
+        let mut world = ripple_formation(r#"["GG", "GG"]"#, EntityDirection::Left);
+        // The bottom left alien, first of the sweep, reaches the left side.
+        let config = world.resource::<GameConfig>().clone();
+        let edge = config.alien_size.x / 2.0 + config.alien_size.x / 8.0;
+        let mut positions: Vec<_> = alien_positions(&mut world).into_iter().collect();
+        positions.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+        let (first, _) = positions[0];
+        world.get_mut::<Transform>(first).unwrap().translation.x = edge;
+
+        // The rest of the sweep goes on towards the side.
+        for _ in 0..4 {
+            let (_, _, step) = ripple_step(&mut world);
+            assert!(step.x < 0.0 && step.y == 0.0);
+        }
+        // The whole next sweep goes down, then the formation heads the other way.
+        for _ in 0..4 {
+            let (_, _, step) = ripple_step(&mut world);
+            assert!(step.x == 0.0 && step.y < 0.0);
+        }
+        let (_, _, step) = ripple_step(&mut world);
+        assert!(step.x > 0.0 && step.y == 0.0);
+    }
+
+    #[test]
+    fn ripple_skips_dead_aliens() {
+        let mut world = ripple_formation(r#"["GGG", "GGG"]"#, EntityDirection::Right);
+        let order: Vec<_> = (0..6).map(|_| ripple_step(&mut world).0).collect();
+
+        // Aliens killed before or during the sweep are skipped without a tick to spare.
+        world.despawn(order[1]);
+        assert_eq!(ripple_step(&mut world).0, order[0]);
+        world.despawn(order[2]);
+        world.despawn(order[5]);
+        assert_eq!(ripple_step(&mut world).0, order[3]);
+        assert_eq!(ripple_step(&mut world).0, order[4]);
+        assert_eq!(ripple_step(&mut world).0, order[0]);
+    }
 }
@@ -0,0 +1,151 @@
+pub mod systems;
+
+use crate::game::input::TickSet;
+use crate::game::GameState;
+use crate::AppState;
+use bevy::prelude::*;
+use systems::*;
+
+/// Rectangle an entity occupies around its translation, in world units, whatever
+/// the scale of its transform.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Collider {
+    pub size: Vec2,
+}
+
+impl Collider {
+    pub fn new(size: Vec2) -> Self {
+        Self { size }
+    }
+
+    pub fn rect(&self, translation: Vec3) -> Rect {
+        Rect::from_center_size(translation.truncate(), self.size)
+    }
+
+    /// Rectangles that only share an edge do not overlap, and an empty one never does.
+    pub fn overlaps(&self, translation: Vec3, other: &Collider, other_translation: Vec3) -> bool {
+        !self
+            .rect(translation)
+            .intersect(other.rect(other_translation))
+            .is_empty()
+    }
+}
+
+/// The collision stage of a tick. Everything moves before it, and the hits it
+/// detects are handled after it.
+#[derive(SystemSet, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CollisionSet;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(FixedUpdate, CollisionSet.in_set(TickSet::Simulation))
+            .add_systems(
+                FixedUpdate,
+                detect_collisions
+                    .in_set(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not(in_state(GameState::Pause))),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(size: Vec2, translation: Vec2, other_size: Vec2, other_translation: Vec2) -> bool {
+        let overlaps = Collider::new(size).overlaps(
+            translation.extend(0.0),
+            &Collider::new(other_size),
+            other_translation.extend(0.0),
+        );
+        // The order of the colliders does not matter.
+        assert_eq!(
+            overlaps,
+            Collider::new(other_size).overlaps(
+                other_translation.extend(0.0),
+                &Collider::new(size),
+                translation.extend(0.0),
+            )
+        );
+        overlaps
+    }
+
+    #[test]
+    fn overlapping() {
+        assert!(overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(1.5, -1.5)
+        ));
+    }
+
+    #[test]
+    fn apart() {
+        assert!(!overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(3.0, 0.0)
+        ));
+        // Close on one axis is not enough.
+        assert!(!overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(0.5, 2.5)
+        ));
+    }
+
+    #[test]
+    fn touching_edges() {
+        assert!(!overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(2.0, 0.0)
+        ));
+        assert!(!overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(2.0, 2.0)
+        ));
+    }
+
+    #[test]
+    fn containment() {
+        assert!(overlaps(
+            Vec2::splat(10.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(1.0, -2.0)
+        ));
+        assert!(overlaps(
+            Vec2::splat(2.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::ZERO
+        ));
+    }
+
+    #[test]
+    fn zero_size() {
+        assert!(!overlaps(
+            Vec2::ZERO,
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::ZERO
+        ));
+        assert!(!overlaps(
+            Vec2::new(0.0, 4.0),
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::ZERO
+        ));
+        assert!(!overlaps(Vec2::ZERO, Vec2::ZERO, Vec2::ZERO, Vec2::ZERO));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::config::GameConfig;
+use crate::game::aliens::{Alien, AlienHit};
+use crate::game::collision::Collider;
+use crate::game::lasers::{Laser, LaserExplosion};
+use crate::game::player::{Player, PlayerHit};
+use crate::game::shelters::{Shelter, ShelterHit};
+use crate::resources::PlayField;
+use bevy::prelude::*;
+
+enum Target<'a> {
+    Alien(Entity, &'a Alien),
+    Player(Entity),
+    Shelter(Entity),
+}
+
+pub fn detect_collisions(
+    mut alien_hit_event_writer: EventWriter<AlienHit>,
+    mut player_hit_event_writer: EventWriter<PlayerHit>,
+    mut shelter_hit_event_writer: EventWriter<ShelterHit>,
+    mut laser_explosion_event_writer: EventWriter<LaserExplosion>,
+    lasers_query: Query<(Entity, &Transform, &Collider, &Laser, Has<Player>)>,
+    player_query: Query<(Entity, &Transform, &Collider), (With<Player>, Without<Laser>)>,
+    aliens_query: Query<(Entity, &Transform, &Collider, &Alien), Without<Laser>>,
+    shelters_query: Query<(Entity, &Transform, &Collider, &Shelter)>,
+    play_field: Res<PlayField>,
+    config: Res<GameConfig>,
+) {
+    // An alien or the player is destroyed by the first laser hitting it, the others go on.
+    let mut destroyed = Vec::new();
+
+    for (laser_entity, laser_transform, laser_collider, laser, from_player) in &lasers_query {
+        let laser_translation = laser_transform.translation;
+        let overlapping = |transform: &Transform, collider: &Collider| {
+            laser_collider.overlaps(laser_translation, collider, transform.translation)
+        };
+
+        let mut targets = Vec::new();
+        if from_player {
+            for (entity, transform, collider, alien) in &aliens_query {
+                if overlapping(transform, collider) && !destroyed.contains(&entity) {
+                    targets.push((Target::Alien(entity, alien), transform.translation));
+                }
+            }
+        } else {
+            for (entity, transform, collider) in &player_query {
+                if overlapping(transform, collider) && !destroyed.contains(&entity) {
+                    targets.push((Target::Player(entity), transform.translation));
+                }
+            }
+        }
+        // Lasers go through the holes of the shelters.
+        for (entity, transform, collider, shelter) in &shelters_query {
+            let laser_rect = laser_collider.rect(laser_translation);
+            if overlapping(transform, collider)
+                && shelter.overlaps(transform.translation, laser_rect)
+            {
+                targets.push((Target::Shelter(entity), transform.translation));
+            }
+        }
+
+        // A laser only hits the first target in its way.
+        let forward = laser.velocity.y.signum();
+        let first_target = targets
+            .into_iter()
+            .min_by(|(_, a), (_, b)| (a.y * forward).total_cmp(&(b.y * forward)));
+
+        match first_target {
+            Some((Target::Alien(entity, alien), translation)) => {
+                destroyed.push(entity);
+                alien_hit_event_writer.send(AlienHit {
+                    alien_type: alien.clone(),
+                    id: entity,
+                    position: translation.truncate(),
+                });
+            }
+            Some((Target::Player(entity), _)) => {
+                destroyed.push(entity);
+                player_hit_event_writer.send(PlayerHit);
+            }
+            Some((Target::Shelter(entity), _)) => {
+                shelter_hit_event_writer.send(ShelterHit {
+                    id: entity,
+                    position: laser_translation.truncate(),
+                });
+            }
+            None => {
+                // Lasers explode at the top of the play field, on the floor and on its sides.
+                let laser_height = config.laser_size.y;
+                let y_bottom = laser_translation.y - laser_height / 2.0;
+                let out_of_bounds = y_bottom > play_field.height() - laser_height
+                    || y_bottom < config.floor_height + config.floor_thickness / 2.0
+                    || laser_translation.x < 0.0
+                    || laser_translation.x > play_field.width();
+                if !out_of_bounds {
+                    continue;
+                }
+            }
+        }
+        laser_explosion_event_writer.send(LaserExplosion(laser_entity));
+    }
+}
@@ -0,0 +1,430 @@
+pub mod systems;
+
+use crate::AppState;
+use bevy::input::gamepad::{
+    GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection, GamepadConnectionEvent,
+    GamepadEvent, GamepadInfo,
+};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use systems::*;
+
+/// Something the player can do, bound to one or more keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    Mute,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Mute,
+        Action::Quit,
+    ];
+}
+
+/// The keys bound to each action.
+///
+/// Actions left out of a config file keep their default keys, and an action bound
+/// to an empty list cannot be performed.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(
+    from = "BTreeMap<Action, Vec<KeyCode>>",
+    into = "BTreeMap<Action, Vec<KeyCode>>"
+)]
+pub struct KeyBindings(pub BTreeMap<Action, Vec<KeyCode>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (Action::MoveLeft, vec![KeyCode::Left, KeyCode::A]),
+            (Action::MoveRight, vec![KeyCode::Right, KeyCode::D]),
+            (Action::Fire, vec![KeyCode::Space]),
+            (Action::Pause, vec![KeyCode::P, KeyCode::Escape]),
+            (Action::Mute, vec![KeyCode::M]),
+            (Action::Quit, vec![KeyCode::Q]),
+        ]))
+    }
+}
+
+impl From<BTreeMap<Action, Vec<KeyCode>>> for KeyBindings {
+    fn from(bindings: BTreeMap<Action, Vec<KeyCode>>) -> Self {
+        let mut key_bindings = Self::default();
+        key_bindings.0.extend(bindings);
+        key_bindings
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<Action, Vec<KeyCode>> {
+    fn from(key_bindings: KeyBindings) -> Self {
+        key_bindings.0
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, action: Action, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// Keys bound to more than one action, along with these actions.
+    pub fn conflicts(&self) -> Vec<(KeyCode, Vec<Action>)> {
+        conflicts(&self.0)
+    }
+
+    /// Add `key` to the keys of `action`. A key only triggers one action, so it is
+    /// taken away from the others.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        for keys in self.0.values_mut() {
+            keys.retain(|other| *other != key);
+        }
+        self.0.entry(action).or_default().push(key);
+    }
+
+    /// Leave `action` without any key.
+    pub fn clear(&mut self, action: Action) {
+        self.0.insert(action, Vec::new());
+    }
+}
+
+/// The gamepad buttons bound to each action, in addition to the left stick which
+/// always moves the player.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(
+    from = "BTreeMap<Action, Vec<GamepadButtonType>>",
+    into = "BTreeMap<Action, Vec<GamepadButtonType>>"
+)]
+pub struct GamepadBindings(pub BTreeMap<Action, Vec<GamepadButtonType>>);
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (Action::MoveLeft, vec![GamepadButtonType::DPadLeft]),
+            (Action::MoveRight, vec![GamepadButtonType::DPadRight]),
+            (
+                Action::Fire,
+                vec![GamepadButtonType::South, GamepadButtonType::RightTrigger2],
+            ),
+            (Action::Pause, vec![GamepadButtonType::Start]),
+            (Action::Mute, vec![]),
+            (Action::Quit, vec![]),
+        ]))
+    }
+}
+
+impl From<BTreeMap<Action, Vec<GamepadButtonType>>> for GamepadBindings {
+    fn from(bindings: BTreeMap<Action, Vec<GamepadButtonType>>) -> Self {
+        let mut gamepad_bindings = Self::default();
+        gamepad_bindings.0.extend(bindings);
+        gamepad_bindings
+    }
+}
+
+impl From<GamepadBindings> for BTreeMap<Action, Vec<GamepadButtonType>> {
+    fn from(gamepad_bindings: GamepadBindings) -> Self {
+        gamepad_bindings.0
+    }
+}
+
+impl GamepadBindings {
+    pub fn buttons(&self, action: Action) -> &[GamepadButtonType] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn pressed(
+        &self,
+        action: Action,
+        gamepad: Gamepad,
+        button_input: &Input<GamepadButton>,
+    ) -> bool {
+        let buttons = self.buttons(action).iter();
+        button_input.any_pressed(buttons.map(|button| GamepadButton::new(gamepad, *button)))
+    }
+
+    pub fn just_pressed(
+        &self,
+        action: Action,
+        gamepad: Gamepad,
+        button_input: &Input<GamepadButton>,
+    ) -> bool {
+        let buttons = self.buttons(action).iter();
+        button_input.any_just_pressed(buttons.map(|button| GamepadButton::new(gamepad, *button)))
+    }
+
+    /// Buttons bound to more than one action, along with these actions.
+    pub fn conflicts(&self) -> Vec<(GamepadButtonType, Vec<Action>)> {
+        conflicts(&self.0)
+    }
+}
+
+fn conflicts<T: Copy + PartialEq>(bindings: &BTreeMap<Action, Vec<T>>) -> Vec<(T, Vec<Action>)> {
+    let mut actions_by_input = Vec::<(T, Vec<Action>)>::new();
+    for (action, inputs) in bindings {
+        for input in inputs {
+            match actions_by_input
+                .iter_mut()
+                .find(|(other, _)| other == input)
+            {
+                Some((_, actions)) if !actions.contains(action) => actions.push(*action),
+                Some(_) => {}
+                None => actions_by_input.push((*input, vec![*action])),
+            }
+        }
+    }
+    actions_by_input.retain(|(_, actions)| actions.len() > 1);
+    actions_by_input
+}
+
+/// The gamepad controlling the player, the first one connected that is still there.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+/// Menu navigation requested this frame, from the keyboard or the active gamepad.
+#[derive(Resource, Clone, Copy, Default, Debug, Eq, PartialEq)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+    /// The pause action, which resumes a paused game from its menu.
+    pub pause: bool,
+}
+
+/// What the player does during one fixed update tick.
+#[derive(Resource, Clone, Copy, Default, Debug, Eq, PartialEq)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    /// Pause or unpause the game.
+    pub pause: bool,
+}
+
+impl PlayerInput {
+    pub fn to_bits(self) -> u8 {
+        self.left as u8 | (self.right as u8) << 1 | (self.fire as u8) << 2 | (self.pause as u8) << 3
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            left: bits & 1 != 0,
+            right: bits & 1 << 1 != 0,
+            fire: bits & 1 << 2 != 0,
+            pause: bits & 1 << 3 != 0,
+        }
+    }
+}
+
+/// Keyboard and gamepad input gathered every frame until the next fixed update tick reads it.
+#[derive(Resource, Default)]
+pub struct BufferedInput(pub PlayerInput);
+
+/// Stages of a fixed update tick. State transitions requested by the previous tick
+/// are applied first, then the input of the tick is read, then the game advances.
+#[derive(SystemSet, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TickSet {
+    ApplyStates,
+    Input,
+    Simulation,
+}
+
+/// Source of the input used by the gameplay systems, the keyboard and the gamepad
+/// unless a replay is being played back.
+#[derive(Resource, Clone, Copy, Default, Debug, Eq, PartialEq)]
+pub enum InputSource {
+    #[default]
+    Live,
+    Replay,
+}
+
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .init_resource::<BufferedInput>()
+            .init_resource::<InputSource>()
+            .configure_sets(
+                FixedUpdate,
+                (TickSet::ApplyStates, TickSet::Input, TickSet::Simulation).chain(),
+            )
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<MenuInput>()
+            .add_systems(
+                PreUpdate,
+                (
+                    handle_gamepad_connections,
+                    (buffer_player_input, read_menu_input),
+                )
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(
+                FixedUpdate,
+                read_buffered_input
+                    .in_set(TickSet::Input)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_equals(InputSource::Live)),
+            );
+    }
+}
+
+/// A virtual gamepad driven through the same events as a real device, so that
+/// gamepad input can be exercised without any hardware, e.g. in automated tests.
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedGamepad(pub Gamepad);
+
+impl SimulatedGamepad {
+    pub fn connect(&self, world: &mut World) {
+        let info = GamepadInfo {
+            name: "Simulated gamepad".to_string(),
+        };
+        self.send(
+            world,
+            GamepadConnectionEvent::new(self.0, GamepadConnection::Connected(info)),
+        );
+    }
+
+    pub fn disconnect(&self, world: &mut World) {
+        self.send(
+            world,
+            GamepadConnectionEvent::new(self.0, GamepadConnection::Disconnected),
+        );
+    }
+
+    pub fn press(&self, world: &mut World, button: GamepadButtonType) {
+        self.send(world, GamepadButtonChangedEvent::new(self.0, button, 1.0));
+    }
+
+    pub fn release(&self, world: &mut World, button: GamepadButtonType) {
+        self.send(world, GamepadButtonChangedEvent::new(self.0, button, 0.0));
+    }
+
+    pub fn move_axis(&self, world: &mut World, axis: GamepadAxisType, value: f32) {
+        self.send(world, GamepadAxisChangedEvent::new(self.0, axis, value));
+    }
+
+    fn send(&self, world: &mut World, event: impl Into<GamepadEvent>) {
+        world.send_event(event.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::game::GameState;
+    use bevy::input::InputPlugin;
+
+    const GAMEPAD: SimulatedGamepad = SimulatedGamepad(Gamepad { id: 0 });
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, PlayerInputPlugin))
+            .init_resource::<GameConfig>()
+            .add_state::<AppState>()
+            .add_state::<GameState>();
+        app.world.insert_resource(NextState(Some(AppState::InGame)));
+        GAMEPAD.connect(&mut app.world);
+        app.update();
+        app
+    }
+
+    /// Input of the next fixed update tick.
+    fn tick(app: &mut App) -> PlayerInput {
+        app.update();
+        app.world.run_schedule(FixedUpdate);
+        *app.world.resource::<PlayerInput>()
+    }
+
+    #[test]
+    fn bind_adds_the_key_and_takes_it_from_other_actions() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.bind(Action::Fire, KeyCode::Up);
+        key_bindings.bind(Action::Fire, KeyCode::A);
+        assert_eq!(
+            key_bindings.keys(Action::Fire),
+            [KeyCode::Space, KeyCode::Up, KeyCode::A]
+        );
+        assert_eq!(key_bindings.keys(Action::MoveLeft), [KeyCode::Left]);
+
+        // Binding a key twice does not repeat it.
+        key_bindings.bind(Action::Fire, KeyCode::Up);
+        assert_eq!(
+            key_bindings.keys(Action::Fire),
+            [KeyCode::Space, KeyCode::A, KeyCode::Up]
+        );
+        assert!(key_bindings.conflicts().is_empty());
+    }
+
+    #[test]
+    fn cleared_actions_stay_unbound() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.clear(Action::Quit);
+        assert!(key_bindings.keys(Action::Quit).is_empty());
+
+        let contents = ron::to_string(&key_bindings).unwrap();
+        let loaded: KeyBindings = ron::from_str(&contents).unwrap();
+        assert!(loaded.keys(Action::Quit).is_empty());
+    }
+
+    #[test]
+    fn simulated_gamepad_becomes_active() {
+        let app = app();
+        assert_eq!(app.world.resource::<ActiveGamepad>().0, Some(GAMEPAD.0));
+    }
+
+    #[test]
+    fn simulated_buttons_resolve_to_actions() {
+        let mut app = app();
+
+        GAMEPAD.press(&mut app.world, GamepadButtonType::South);
+        GAMEPAD.press(&mut app.world, GamepadButtonType::DPadLeft);
+        let input = tick(&mut app);
+        assert!(input.fire && input.left && !input.right);
+
+        GAMEPAD.release(&mut app.world, GamepadButtonType::South);
+        GAMEPAD.release(&mut app.world, GamepadButtonType::DPadLeft);
+        assert_eq!(tick(&mut app), PlayerInput::default());
+    }
+
+    #[test]
+    fn simulated_stick_respects_dead_zone() {
+        let mut app = app();
+        let dead_zone = app.world.resource::<GameConfig>().gamepad_dead_zone;
+
+        GAMEPAD.move_axis(&mut app.world, GamepadAxisType::LeftStickX, dead_zone / 2.0);
+        assert_eq!(tick(&mut app), PlayerInput::default());
+
+        GAMEPAD.move_axis(&mut app.world, GamepadAxisType::LeftStickX, 1.0);
+        let input = tick(&mut app);
+        assert!(input.right && !input.left);
+    }
+
+    #[test]
+    fn disconnecting_pauses_the_game() {
+        let mut app = app();
+        GAMEPAD.disconnect(&mut app.world);
+        assert!(tick(&mut app).pause);
+        assert_eq!(app.world.resource::<ActiveGamepad>().0, None);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::config::GameConfig;
+use crate::game::input::{Action, ActiveGamepad, BufferedInput, MenuInput, PlayerInput};
+use crate::game::GameState;
+use crate::user_settings::UserSettings;
+use crate::AppState;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+use bevy::window::{WindowFocused, WindowResized};
+
+pub fn handle_gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    app_state: Res<State<AppState>>,
+    game_state: Res<State<GameState>>,
+    mut active_gamepad: ResMut<ActiveGamepad>,
+    mut buffered_input: ResMut<BufferedInput>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                if active_gamepad.0.is_none() {
+                    info!("Playing with {}", info.name);
+                    active_gamepad.0 = Some(event.gamepad);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                if active_gamepad.0 == Some(event.gamepad) {
+                    // Hand over to another gamepad if there is one, the keyboard always works.
+                    active_gamepad.0 = gamepads.iter().find(|gamepad| *gamepad != event.gamepad);
+                    let playing = *app_state.get() == AppState::InGame
+                        && *game_state.get() == GameState::Running;
+                    if playing {
+                        buffered_input.0.pause = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Pause the game when the player switches to another window or minimises this one,
+/// the same way as pressing the pause key.
+pub fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut resize_events: EventReader<WindowResized>,
+    settings: Res<UserSettings>,
+    app_state: Res<State<AppState>>,
+    game_state: Res<State<GameState>>,
+    mut buffered_input: ResMut<BufferedInput>,
+) {
+    let focus_lost = focus_events.read().any(|event| !event.focused);
+    let minimised = resize_events
+        .read()
+        .any(|event| event.width == 0.0 || event.height == 0.0);
+    let playing = *app_state.get() == AppState::InGame && *game_state.get() == GameState::Running;
+    if (focus_lost || minimised) && settings.auto_pause && playing {
+        buffered_input.0.pause = true;
+    }
+}
+
+pub fn buffer_player_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    active_gamepad: Res<ActiveGamepad>,
+    config: Res<GameConfig>,
+    game_state: Res<State<GameState>>,
+    mut buffered_input: ResMut<BufferedInput>,
+) {
+    let keys = &config.key_bindings;
+    let pressed = |action| {
+        keys.pressed(action, &keyboard_input)
+            || active_gamepad.0.is_some_and(|gamepad| {
+                config
+                    .gamepad_bindings
+                    .pressed(action, gamepad, &button_input)
+            })
+    };
+    let just_pressed = |action| {
+        keys.just_pressed(action, &keyboard_input)
+            || active_gamepad.0.is_some_and(|gamepad| {
+                config
+                    .gamepad_bindings
+                    .just_pressed(action, gamepad, &button_input)
+            })
+    };
+    let stick_x = active_gamepad
+        .0
+        .and_then(|gamepad| axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)))
+        .unwrap_or_default();
+
+    let input = &mut buffered_input.0;
+    input.left = pressed(Action::MoveLeft) || stick_x < -config.gamepad_dead_zone;
+    input.right = pressed(Action::MoveRight) || stick_x > config.gamepad_dead_zone;
+    input.fire = pressed(Action::Fire);
+    // Keep the key press until a tick reads it, there may be no tick this frame.
+    // A paused game is resumed from the pause menu, which counts down first.
+    input.pause |= just_pressed(Action::Pause) && *game_state.get() != GameState::Pause;
+}
+
+pub fn read_buffered_input(
+    mut buffered_input: ResMut<BufferedInput>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    *player_input = buffered_input.0;
+    buffered_input.0.pause = false;
+}
+
+pub fn read_menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    active_gamepad: Res<ActiveGamepad>,
+    config: Res<GameConfig>,
+    mut previous_stick: Local<IVec2>,
+    mut menu_input: ResMut<MenuInput>,
+) {
+    let button = |button_type| {
+        active_gamepad.0.is_some_and(|gamepad| {
+            button_input.just_pressed(GamepadButton::new(gamepad, button_type))
+        })
+    };
+    let axis = |axis_type| {
+        active_gamepad
+            .0
+            .and_then(|gamepad| axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .unwrap_or_default()
+    };
+    let direction = |value: f32| {
+        if value > config.gamepad_dead_zone {
+            1
+        } else if value < -config.gamepad_dead_zone {
+            -1
+        } else {
+            0
+        }
+    };
+
+    // The stick acts like a button: tilting it moves the selection once.
+    let stick = IVec2::new(
+        direction(axis(GamepadAxisType::LeftStickX)),
+        direction(axis(GamepadAxisType::LeftStickY)),
+    );
+    let tilted =
+        |current: i32, previous: i32, towards: i32| current == towards && previous != towards;
+    let stick_up = tilted(stick.y, previous_stick.y, 1);
+    let stick_down = tilted(stick.y, previous_stick.y, -1);
+    let stick_left = tilted(stick.x, previous_stick.x, -1);
+    let stick_right = tilted(stick.x, previous_stick.x, 1);
+    *previous_stick = stick;
+
+    *menu_input = MenuInput {
+        up: keyboard_input.just_pressed(KeyCode::Up)
+            || button(GamepadButtonType::DPadUp)
+            || stick_up,
+        down: keyboard_input.just_pressed(KeyCode::Down)
+            || button(GamepadButtonType::DPadDown)
+            || stick_down,
+        left: keyboard_input.just_pressed(KeyCode::Left)
+            || button(GamepadButtonType::DPadLeft)
+            || stick_left,
+        right: keyboard_input.just_pressed(KeyCode::Right)
+            || button(GamepadButtonType::DPadRight)
+            || stick_right,
+        confirm: keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space])
+            || button(GamepadButtonType::South),
+        back: keyboard_input.any_just_pressed([KeyCode::Back, KeyCode::Escape])
+            || button(GamepadButtonType::East),
+        pause: config
+            .key_bindings
+            .just_pressed(Action::Pause, &keyboard_input)
+            || active_gamepad.0.is_some_and(|gamepad| {
+                config
+                    .gamepad_bindings
+                    .just_pressed(Action::Pause, gamepad, &button_input)
+            }),
+    };
+}
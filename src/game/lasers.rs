@@ -1,8 +1,12 @@
 pub mod systems;
 
-use crate::game::{EntityDirection, GameState};
+use crate::config::GameConfig;
+use crate::game::collision::CollisionSet;
+use crate::game::input::TickSet;
+use crate::game::GameState;
 use crate::AppState;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use systems::*;
 
 #[derive(Event)]
@@ -10,11 +14,73 @@ pub struct LaserExplosion(pub Entity);
 
 #[derive(Component)]
 pub struct Laser {
-    pub direction: EntityDirection,
-    pub speed: f32,
+    /// In world units per second.
+    pub velocity: Vec2,
     pub source: Option<Entity>,
 }
 
+/// Number of frames of each projectile in the projectile sprite sheet.
+pub const PROJECTILE_FRAMES: usize = 4;
+
+/// Time between two frames of a projectile.
+const PROJECTILE_FRAME_DURATION: f32 = 0.06;
+
+/// Kind of shot fired by an alien, each with its own look and motion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Projectile {
+    /// Falls straight down, faster than the others.
+    Rolling,
+    /// Aimed at where the player stands when it is fired.
+    Plunger,
+    /// Zigzags on its way down, slower than the others.
+    Squiggly,
+}
+
+impl Projectile {
+    pub fn speed(self, config: &GameConfig) -> f32 {
+        let factor = match self {
+            Projectile::Rolling => 1.25,
+            Projectile::Plunger => 1.0,
+            Projectile::Squiggly => 0.8,
+        };
+        config.alien_laser_speed * factor
+    }
+
+    /// Row of the projectile in the projectile sprite sheet.
+    pub fn sheet_row(self) -> usize {
+        match self {
+            Projectile::Rolling => 0,
+            Projectile::Plunger => 1,
+            Projectile::Squiggly => 2,
+        }
+    }
+}
+
+/// Side to side motion of a laser, a sine of the time since it was fired.
+#[derive(Component)]
+pub struct Zigzag {
+    pub amplitude: f32,
+    /// Oscillations per second.
+    pub frequency: f32,
+    pub elapsed: f32,
+}
+
+/// Cycles a projectile through its frames.
+#[derive(Component)]
+pub struct ProjectileAnimation {
+    pub first_frame: usize,
+    pub timer: Timer,
+}
+
+impl ProjectileAnimation {
+    pub fn new(projectile: Projectile) -> Self {
+        Self {
+            first_frame: projectile.sheet_row() * PROJECTILE_FRAMES,
+            timer: Timer::from_seconds(PROJECTILE_FRAME_DURATION, TimerMode::Repeating),
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct ExplosionTimer(pub Timer);
 
@@ -24,15 +90,24 @@ impl Plugin for LasersPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LaserExplosion>()
             .add_systems(
-                Update,
-                (move_lasers, despawn_lasers, check_for_collisions)
-                    .chain()
+                FixedUpdate,
+                move_lasers
+                    .in_set(TickSet::Simulation)
+                    .before(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not(in_state(GameState::Pause))),
+            )
+            .add_systems(
+                FixedUpdate,
+                handle_laser_explosion
+                    .in_set(TickSet::Simulation)
+                    .after(CollisionSet)
                     .run_if(in_state(AppState::InGame))
                     .run_if(not(in_state(GameState::Pause))),
             )
             .add_systems(
                 Update,
-                (handle_laser_explosion, update_xp_texts)
+                (update_xp_texts, animate_projectiles)
                     .run_if(in_state(AppState::InGame))
                     .run_if(not(in_state(GameState::Pause))),
             );
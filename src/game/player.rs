@@ -1,5 +1,7 @@
 pub mod systems;
 
+use crate::game::collision::CollisionSet;
+use crate::game::input::TickSet;
 use crate::game::GameState;
 use crate::AppState;
 use bevy::prelude::*;
@@ -21,12 +23,16 @@ impl Plugin for PlayerPlugin {
                 FixedUpdate,
                 (move_player, restrict_player_movement)
                     .chain()
+                    .in_set(TickSet::Simulation)
+                    .before(CollisionSet)
                     .run_if(in_state(AppState::InGame))
                     .run_if(not(in_state(GameState::Pause))),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
                 (player_shoot, handle_player_hit)
+                    .in_set(TickSet::Simulation)
+                    .after(CollisionSet)
                     .run_if(in_state(AppState::InGame))
                     .run_if(not(in_state(GameState::Pause))),
             );
@@ -1,65 +1,63 @@
+use crate::config::GameConfig;
+use crate::game::collision::Collider;
+use crate::game::input::PlayerInput;
 use crate::game::lasers::Laser;
 use crate::game::player::{Player, PlayerHit};
-use crate::game::{EntityDirection, GameOver, GameState, OnGameScreen};
-use crate::resources::{ExplosionSound, LivesRemaining, ShootSound};
-use crate::settings::{
-    FLOOR_HEIGHT, FLOOR_THICKNESS, LASER_SIZE, PLAYER_LASER_SPEED, PLAYER_SIZE, PLAYER_SPEED,
-};
+use crate::game::{GameEvent, GameOver, GameState, OnGameScreen};
+use crate::resources::{LivesRemaining, PlayField, ShotsFired};
 use bevy::asset::AssetServer;
-use bevy::audio::{AudioBundle, PlaybackSettings};
-use bevy::input::Input;
-use bevy::math::Vec3;
+use bevy::math::{Vec2, Vec3};
 use bevy::prelude::{
-    default, Color, Commands, Entity, EventReader, EventWriter, KeyCode, NextState, Query, Res,
-    ResMut, Sprite, SpriteBundle, Time, Transform, Window, With, Without,
+    default, Color, Commands, Entity, EventReader, EventWriter, NextState, Query, Res, ResMut,
+    Sprite, SpriteBundle, Time, Transform, With, Without,
 };
-use bevy::window::PrimaryWindow;
 
 pub fn spawn_player(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    play_field: Res<PlayField>,
     asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
 ) {
-    let window = window_query.single();
-    let y_pos = FLOOR_HEIGHT + PLAYER_SIZE.y / 2.0 + FLOOR_THICKNESS / 2.0;
+    let y_pos = config.floor_height + config.player_size.y / 2.0 + config.floor_thickness / 2.0;
     commands.spawn((
         SpriteBundle {
             texture: asset_server.load("sprites/player.png"),
-            transform: Transform::from_xyz(window.width() / 2.0, y_pos, 0.0),
+            transform: Transform::from_xyz(play_field.width() / 2.0, y_pos, 0.0),
             ..default()
         },
         Player,
+        Collider::new(config.player_size),
         OnGameScreen,
     ));
 }
 
 pub fn move_player(
     mut player_query: Query<&mut Transform, (With<Player>, Without<Laser>)>,
-    keyboard_input: Res<Input<KeyCode>>,
+    player_input: Res<PlayerInput>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
     let mut movement = Vec3::ZERO;
 
-    if keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left) {
+    if player_input.left {
         movement.x = -1.0;
-    } else if keyboard_input.pressed(KeyCode::I) || keyboard_input.pressed(KeyCode::Right) {
+    } else if player_input.right {
         movement.x = 1.0;
     }
 
     if let Ok(mut transform) = player_query.get_single_mut() {
-        transform.translation += movement * PLAYER_SPEED * time.delta_seconds();
+        transform.translation += movement * config.player_speed * time.delta_seconds();
     }
 }
 
 pub fn restrict_player_movement(
     mut player_query: Query<&mut Transform, (With<Player>, Without<Laser>)>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    play_field: Res<PlayField>,
+    config: Res<GameConfig>,
 ) {
-    let window = window_query.single();
-
-    let half_player_width = PLAYER_SIZE.x / 2.0;
+    let half_player_width = config.player_size.x / 2.0;
     let x_min = half_player_width;
-    let x_max = window.width() - half_player_width;
+    let x_max = play_field.width() - half_player_width;
 
     if let Ok(mut transform) = player_query.get_single_mut() {
         transform.translation.x = transform.translation.x.clamp(x_min, x_max);
@@ -70,13 +68,16 @@ pub fn player_shoot(
     mut commands: Commands,
     player_query: Query<&Transform, With<Player>>,
     laser_query: Query<&Laser, With<Player>>,
-    keyboard_input: Res<Input<KeyCode>>,
-    shoot_sound: Res<ShootSound>,
+    player_input: Res<PlayerInput>,
+    config: Res<GameConfig>,
+    mut game_events: EventWriter<GameEvent>,
+    mut shots_fired: ResMut<ShotsFired>,
 ) {
-    if laser_query.get_single().is_err() && keyboard_input.pressed(KeyCode::Space) {
+    if laser_query.get_single().is_err() && player_input.fire {
         if let Ok(player_transform) = player_query.get_single() {
             let translation = player_transform.translation;
-            let half_player_height = PLAYER_SIZE.x / 2.0;
+            let half_player_height = config.player_size.x / 2.0;
+            shots_fired.0 += 1;
             // Spawn a new laser shot by the player.
             commands.spawn((
                 SpriteBundle {
@@ -90,22 +91,21 @@ pub fn player_shoot(
                             translation.y + half_player_height,
                             0.0,
                         ),
-                        scale: LASER_SIZE.extend(0.0),
+                        scale: config.laser_size.extend(0.0),
                         ..default()
                     },
                     ..default()
                 },
                 Laser {
-                    direction: EntityDirection::Up,
-                    speed: PLAYER_LASER_SPEED,
+                    velocity: Vec2::new(0.0, config.player_laser_speed),
                     source: None,
                 },
+                Collider::new(config.laser_size),
                 Player,
                 OnGameScreen,
             ));
-            commands.spawn(AudioBundle {
-                source: shoot_sound.0.clone(),
-                settings: PlaybackSettings::DESPAWN,
+            game_events.send(GameEvent::Shot {
+                position: translation,
             });
         }
     }
@@ -115,19 +115,17 @@ pub fn handle_player_hit(
     mut commands: Commands,
     mut player_hit_event_reader: EventReader<PlayerHit>,
     mut game_over_event_writer: EventWriter<GameOver>,
-    player_query: Query<Entity, (With<Player>, Without<Laser>)>,
-    explosion_sound: Res<ExplosionSound>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Laser>)>,
+    mut game_events: EventWriter<GameEvent>,
     mut lives_remaining: ResMut<LivesRemaining>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
     if player_hit_event_reader.read().next().is_some() {
-        if let Ok(player_entity) = player_query.get_single() {
+        if let Ok((player_entity, player_transform)) = player_query.get_single() {
             commands.entity(player_entity).despawn();
 
-            // Play an explosion sound when the player dies.
-            commands.spawn(AudioBundle {
-                source: explosion_sound.0.clone(),
-                settings: PlaybackSettings::DESPAWN,
+            game_events.send(GameEvent::PlayerKilled {
+                position: player_transform.translation,
             });
 
             // Decrease the number of lives remaining.
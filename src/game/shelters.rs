@@ -1,28 +1,245 @@
 pub mod systems;
 
+use crate::game::collision::systems::detect_collisions;
+use crate::game::collision::CollisionSet;
+use crate::game::input::TickSet;
+use crate::game::systems::reset_game_state;
 use crate::game::GameState;
+use crate::resources::GameRng;
+use crate::storage;
 use crate::AppState;
 use bevy::prelude::*;
+use image::{ImageResult, Rgba, RgbaImage};
+use rand::Rng;
+use std::ops::Range;
 use systems::*;
 
-#[derive(Component)]
+const BITMAP_PATH: &str = "sprites/shelter.png";
+/// Size of the plain bitmap used when the shelter image cannot be read.
+const FALLBACK_SIZE: (u32, u32) = (22, 16);
+
+/// The bitmap every shelter starts from.
+#[derive(Resource)]
+pub struct ShelterBitmap(pub RgbaImage);
+
+impl ShelterBitmap {
+    pub fn load() -> ImageResult<Self> {
+        let image = image::open(storage::asset_path(BITMAP_PATH))?.into_rgba8();
+        Ok(Self(image))
+    }
+
+    /// A bitmap with every pixel standing.
+    pub fn solid(width: u32, height: u32) -> Self {
+        Self(RgbaImage::from_pixel(width, height, Rgba([255; 4])))
+    }
+}
+
+/// A shelter worn away pixel by pixel by the lasers and the aliens going through it.
+///
+/// The pixels of its bitmap are stretched over `size`, centred on the translation
+/// of the shelter.
+#[derive(Component, Clone, Debug)]
 pub struct Shelter {
-    pub armor: u32,
+    pub size: Vec2,
+    pub width: usize,
+    pub height: usize,
+    /// Whether each pixel still stands, row by row from the top.
+    pub pixels: Vec<bool>,
+}
+
+impl Shelter {
+    pub fn new(bitmap: &ShelterBitmap, size: Vec2) -> Self {
+        let pixels = bitmap.0.pixels().map(|pixel| pixel[3] > 0).collect();
+        Self {
+            size,
+            width: bitmap.0.width() as usize,
+            height: bitmap.0.height() as usize,
+            pixels,
+        }
+    }
+
+    fn pixel_size(&self) -> Vec2 {
+        self.size / Vec2::new(self.width as f32, self.height as f32)
+    }
+
+    /// Columns and rows of the pixels covered by `rect`, for a shelter at `translation`.
+    fn pixels_in(&self, translation: Vec3, rect: Rect) -> (Range<usize>, Range<usize>) {
+        let bounds = Rect::from_center_size(translation.truncate(), self.size);
+        let pixel_size = self.pixel_size();
+        let column = |x: f32| ((x - bounds.min.x) / pixel_size.x).clamp(0.0, self.width as f32);
+        let row = |y: f32| ((bounds.max.y - y) / pixel_size.y).clamp(0.0, self.height as f32);
+        (
+            column(rect.min.x).floor() as usize..column(rect.max.x).ceil() as usize,
+            row(rect.max.y).floor() as usize..row(rect.min.y).ceil() as usize,
+        )
+    }
+
+    /// Whether any pixel still standing lies in `rect`.
+    pub fn overlaps(&self, translation: Vec3, rect: Rect) -> bool {
+        let (columns, rows) = self.pixels_in(translation, rect);
+        rows.into_iter()
+            .any(|y| columns.clone().any(|x| self.pixels[y * self.width + x]))
+    }
+
+    /// Clear every pixel in `rect`.
+    pub fn erode_rect(&mut self, translation: Vec3, rect: Rect) {
+        let (columns, rows) = self.pixels_in(translation, rect);
+        for y in rows {
+            for x in columns.clone() {
+                self.pixels[y * self.width + x] = false;
+            }
+        }
+    }
+
+    /// Blow a ragged hole of `radius` around `center`: pixels closer than half the
+    /// radius always go, the others in the radius go at random.
+    pub fn erode_blast(&mut self, translation: Vec3, center: Vec2, radius: f32, rng: &mut GameRng) {
+        let rect = Rect::from_center_half_size(center, Vec2::splat(radius));
+        let (columns, rows) = self.pixels_in(translation, rect);
+        let pixel_size = self.pixel_size();
+        let top_left = translation.truncate() + Vec2::new(-self.size.x, self.size.y) / 2.0;
+        for y in rows {
+            for x in columns.clone() {
+                let pixel_center =
+                    top_left + Vec2::new(x as f32 + 0.5, -(y as f32 + 0.5)) * pixel_size;
+                let distance = pixel_center.distance(center) / radius;
+                if distance < 0.5 || (distance < 1.0 && rng.gen_bool(0.5)) {
+                    self.pixels[y * self.width + x] = false;
+                }
+            }
+        }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        !self.pixels.contains(&true)
+    }
 }
 
-#[derive(Component)]
-pub struct ShelterArmorText(pub Entity);
+#[derive(Event)]
+pub struct ShelterHit {
+    pub id: Entity,
+    /// Where the laser hit it.
+    pub position: Vec2,
+}
 
 pub struct SheltersPlugin;
 
 impl Plugin for SheltersPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::InGame), spawn_shelters)
+        let bitmap = ShelterBitmap::load().unwrap_or_else(|err| {
+            error!("Failed to open the shelter bitmap, using plain blocks instead: {err}");
+            ShelterBitmap::solid(FALLBACK_SIZE.0, FALLBACK_SIZE.1)
+        });
+        app.insert_resource(bitmap)
+            .add_event::<ShelterHit>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                spawn_shelters.after(reset_game_state),
+            )
             .add_systems(
                 FixedUpdate,
-                shelter_hit
+                erode_shelters_under_aliens
+                    .in_set(CollisionSet)
+                    .before(detect_collisions)
                     .run_if(in_state(AppState::InGame))
                     .run_if(in_state(GameState::Running)),
-            );
+            )
+            .add_systems(
+                FixedUpdate,
+                handle_shelter_hit
+                    .in_set(TickSet::Simulation)
+                    .after(CollisionSet)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(Update, update_shelter_images);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A shelter of `width` by `height` pixels of one world unit each, centred on the origin.
+    fn shelter(width: u32, height: u32) -> Shelter {
+        let size = Vec2::new(width as f32, height as f32);
+        Shelter::new(&ShelterBitmap::solid(width, height), size)
+    }
+
+    fn standing(shelter: &Shelter, x: usize, y: usize) -> bool {
+        shelter.pixels[y * shelter.width + x]
+    }
+
+    #[test]
+    fn pixels_in_whole_shelter() {
+        let shelter = shelter(4, 2);
+        let rect = Rect::from_center_size(Vec2::ZERO, Vec2::new(4.0, 2.0));
+        assert_eq!(shelter.pixels_in(Vec3::ZERO, rect), (0..4, 0..2));
+    }
+
+    #[test]
+    fn pixels_in_partly_covered() {
+        let shelter = shelter(4, 2);
+        // Covers half of the two middle columns of the top row.
+        let rect = Rect::new(-0.5, 0.2, 0.5, 0.8);
+        assert_eq!(shelter.pixels_in(Vec3::ZERO, rect), (1..3, 0..1));
+        // Same place relative to a shelter moved elsewhere.
+        let translation = Vec3::new(10.0, -5.0, 0.0);
+        let moved = Rect::new(9.5, -4.8, 10.5, -4.2);
+        assert_eq!(shelter.pixels_in(translation, moved), (1..3, 0..1));
+    }
+
+    #[test]
+    fn pixels_in_clamped_to_shelter() {
+        let shelter = shelter(4, 2);
+        let rect = Rect::new(1.5, -10.0, 10.0, 10.0);
+        assert_eq!(shelter.pixels_in(Vec3::ZERO, rect), (3..4, 0..2));
+        let outside = Rect::new(5.0, 5.0, 6.0, 6.0);
+        let (columns, rows) = shelter.pixels_in(Vec3::ZERO, outside);
+        assert!(columns.is_empty() || rows.is_empty());
+        assert!(!shelter.overlaps(Vec3::ZERO, outside));
+    }
+
+    #[test]
+    fn eroded_pixels_stop_overlapping() {
+        let mut shelter = shelter(4, 2);
+        let rect = Rect::new(-0.5, 0.2, 0.5, 0.8);
+        assert!(shelter.overlaps(Vec3::ZERO, rect));
+        shelter.erode_rect(Vec3::ZERO, rect);
+        assert!(!shelter.overlaps(Vec3::ZERO, rect));
+        assert!(!standing(&shelter, 1, 0) && !standing(&shelter, 2, 0));
+        assert_eq!(shelter.pixels.iter().filter(|pixel| **pixel).count(), 6);
+    }
+
+    #[test]
+    fn erode_blast_clears_the_core_and_spares_the_outside() {
+        for seed in 0..8 {
+            let mut shelter = shelter(9, 9);
+            let mut rng = GameRng::new(Some(seed));
+            shelter.erode_blast(Vec3::ZERO, Vec2::ZERO, 4.0, &mut rng);
+
+            for y in 0..9 {
+                for x in 0..9 {
+                    let center = Vec2::new(x as f32 - 4.0, 4.0 - y as f32);
+                    let distance = center.length() / 4.0;
+                    if distance < 0.5 {
+                        assert!(!standing(&shelter, x, y), "({x}, {y}) kept");
+                    } else if distance >= 1.0 {
+                        assert!(standing(&shelter, x, y), "({x}, {y}) cleared");
+                    }
+                }
+            }
+            assert!(!shelter.is_destroyed());
+        }
+    }
+
+    #[test]
+    fn erode_blast_is_deterministic() {
+        let blast = |seed| {
+            let mut shelter = shelter(9, 9);
+            shelter.erode_blast(Vec3::ZERO, Vec2::ZERO, 4.0, &mut GameRng::new(Some(seed)));
+            shelter.pixels
+        };
+        assert_eq!(blast(3), blast(3));
     }
 }
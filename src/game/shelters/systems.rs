@@ -1,99 +1,125 @@
-use crate::game::lasers::{Laser, LaserExplosion};
-use crate::game::shelters::{Shelter, ShelterArmorText};
+use crate::config::GameConfig;
+use crate::game::aliens::Alien;
+use crate::game::collision::Collider;
+use crate::game::lasers::Laser;
+use crate::game::shelters::{Shelter, ShelterBitmap, ShelterHit};
+use crate::game::waves::Waves;
 use crate::game::OnGameScreen;
-use crate::settings::*;
-use bevy::asset::AssetServer;
+use crate::resources::{CurrentWave, GameRng, PlayField};
 use bevy::math::Vec3;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
 pub fn spawn_shelters(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    asset_server: Res<AssetServer>,
+    play_field: Res<PlayField>,
+    bitmap: Res<ShelterBitmap>,
+    config: Res<GameConfig>,
+    waves: Res<Waves>,
+    current_wave: Res<CurrentWave>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    let window = window_query.single();
-
-    let sprite = asset_server.load("sprites/shelter.png");
-    let font = asset_server.load("fonts/font.ttf");
+    let shelter_size = config.shelter_size;
+    let num_shelters = waves.get(current_wave.0).shelters;
 
     let space_between_shelters =
-        (window.width() - NUM_SHELTERS as f32 * SHELTER_SIZE.x) / (NUM_SHELTERS + 1) as f32;
-    let height_below_shelter = 2.0 * FLOOR_HEIGHT + PLAYER_SIZE.y;
+        (play_field.width() - num_shelters as f32 * shelter_size.x) / (num_shelters + 1) as f32;
+    let height_below_shelter = 2.0 * config.floor_height + config.player_size.y;
     let mut translation = Vec3::new(
-        space_between_shelters + SHELTER_SIZE.x / 2.0,
+        space_between_shelters + shelter_size.x / 2.0,
         height_below_shelter,
         1.0,
     );
 
-    let text_style = TextStyle {
-        color: Color::WHITE,
-        font: font.clone(),
-        font_size: 20.0,
-    };
-
-    for _ in 0..NUM_SHELTERS {
-        commands
-            .spawn((
-                SpriteBundle {
-                    texture: sprite.clone(),
-                    transform: Transform {
-                        translation,
-                        ..default()
-                    },
+    for _ in 0..num_shelters {
+        // Each shelter wears away on its own, so each one draws its own copy of the bitmap.
+        let image = Image::new(
+            Extent3d {
+                width: bitmap.0.width(),
+                height: bitmap.0.height(),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            bitmap.0.clone().into_raw(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(shelter_size),
                     ..default()
                 },
-                Shelter {
-                    armor: INITIAL_ARMOR_VALUE,
+                texture: images.add(image),
+                transform: Transform {
+                    translation,
+                    ..default()
                 },
-                OnGameScreen,
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    Text2dBundle {
-                        text: Text::from_section(
-                            INITIAL_ARMOR_VALUE.to_string(),
-                            text_style.clone(),
-                        )
-                        .with_alignment(TextAlignment::Center),
-                        transform: Transform::from_translation(Vec3::new(0.0, -40.0, 0.0)),
-                        ..default()
-                    },
-                    ShelterArmorText(parent.parent_entity()),
-                ));
-            });
+                ..default()
+            },
+            Shelter::new(&bitmap, shelter_size),
+            Collider::new(shelter_size),
+            OnGameScreen,
+        ));
 
-        translation.x += space_between_shelters + SHELTER_SIZE.x;
+        translation.x += space_between_shelters + shelter_size.x;
     }
 }
 
-pub fn shelter_hit(
+pub fn erode_shelters_under_aliens(
     mut commands: Commands,
-    mut laser_explosion_event_writer: EventWriter<LaserExplosion>,
-    mut shelters_query: Query<(Entity, &Transform, &mut Shelter)>,
-    mut armor_texts_query: Query<(&mut Text, &mut ShelterArmorText)>,
-    lasers_query: Query<(Entity, &Transform), With<Laser>>,
+    aliens_query: Query<(&Transform, &Collider), (With<Alien>, Without<Laser>)>,
+    mut shelters_query: Query<(Entity, &Transform, &Collider, &mut Shelter)>,
 ) {
-    for (laser_entity, laser_transform) in lasers_query.iter() {
-        for (shelter_entity, shelter_transform, mut shelter) in shelters_query.iter_mut() {
-            if shelter_transform
-                .translation
-                .distance(laser_transform.translation)
-                <= SHELTER_SIZE.x / 2.0 + LASER_SIZE.x / 2.0
+    for (shelter_entity, shelter_transform, shelter_collider, mut shelter) in &mut shelters_query {
+        let shelter_translation = shelter_transform.translation;
+        for (alien_transform, alien_collider) in &aliens_query {
+            let alien_translation = alien_transform.translation;
+            if alien_collider.overlaps(alien_translation, shelter_collider, shelter_translation)
+                && shelter.overlaps(shelter_translation, alien_collider.rect(alien_translation))
             {
-                laser_explosion_event_writer.send(LaserExplosion(laser_entity));
-                shelter.armor = shelter.armor.saturating_sub(LASER_DAMAGE);
+                shelter.erode_rect(shelter_translation, alien_collider.rect(alien_translation));
+            }
+        }
+        if shelter.is_destroyed() {
+            commands.entity(shelter_entity).despawn();
+        }
+    }
+}
 
-                // Retrieve the armor text corresponding to this shelter.
-                let (mut text, _) = armor_texts_query
-                    .iter_mut()
-                    .find(|(_, t)| t.0 == shelter_entity)
-                    .unwrap();
-                text.sections[0].value = shelter.armor.to_string();
+pub fn handle_shelter_hit(
+    mut commands: Commands,
+    mut shelter_hit_event_reader: EventReader<ShelterHit>,
+    mut shelters_query: Query<(&Transform, &mut Shelter)>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    for ShelterHit { id, position } in shelter_hit_event_reader.read() {
+        let Ok((transform, mut shelter)) = shelters_query.get_mut(*id) else {
+            continue;
+        };
+        shelter.erode_blast(
+            transform.translation,
+            *position,
+            config.shelter_blast_radius,
+            &mut rng,
+        );
+        if shelter.is_destroyed() {
+            commands.entity(*id).despawn();
+        }
+    }
+}
 
-                if shelter.armor == 0 {
-                    commands.entity(shelter_entity).despawn_recursive();
-                }
+pub fn update_shelter_images(
+    shelters_query: Query<(&Shelter, &Handle<Image>), Changed<Shelter>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (shelter, handle) in &shelters_query {
+        let Some(image) = images.get_mut(handle) else {
+            continue;
+        };
+        for (pixel, standing) in image.data.chunks_exact_mut(4).zip(&shelter.pixels) {
+            if !standing {
+                pixel[3] = 0;
             }
         }
     }
@@ -1,7 +1,10 @@
-use crate::game::{GameOver, GameState, OnGameScreen};
+use crate::config::GameConfig;
+use crate::game::input::PlayerInput;
+use crate::game::{EntityDirection, GameOver, GameState, OnGameScreen};
+use crate::high_scores::HighScores;
 use crate::resources::*;
 use crate::settings::*;
-use crate::*;
+use crate::AppState;
 use bevy::core::FrameCount;
 use bevy::prelude::*;
 
@@ -11,8 +14,8 @@ pub fn make_visible(mut window: Query<&mut Window>, frames: Res<FrameCount>) {
     }
 }
 
-pub fn spawn_floor(mut commands: Commands) {
-    let window_width = get_window_resolution().x;
+pub fn spawn_floor(mut commands: Commands, config: Res<GameConfig>, play_field: Res<PlayField>) {
+    let width = play_field.width();
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
@@ -20,8 +23,8 @@ pub fn spawn_floor(mut commands: Commands) {
                 ..default()
             },
             transform: Transform {
-                translation: Vec3::new(window_width / 2.0, FLOOR_HEIGHT, 1.0),
-                scale: Vec3::new(window_width, FLOOR_THICKNESS, 0.0),
+                translation: Vec3::new(width / 2.0, config.floor_height, 1.0),
+                scale: Vec3::new(width, config.floor_thickness, 0.0),
                 ..default()
             },
             ..default()
@@ -41,10 +44,78 @@ pub fn handle_game_over(
     }
 }
 
+/// Keep the score of a finished game as the best one if it is, and tell where to go
+/// next: the name entry screen if it makes it into the high scores, else the menu.
+pub fn finish_game(
+    score: &PlayerScore,
+    best_score: &mut BestScore,
+    high_scores: Option<&HighScores>,
+    replaying: bool,
+) -> AppState {
+    if score.0 > best_score.0 {
+        best_score.0 = score.0;
+    }
+    // Replayed games already had their chance to enter the table.
+    let new_high_score =
+        !replaying && high_scores.is_some_and(|high_scores| high_scores.qualifies(score.0));
+    if new_high_score {
+        AppState::NameEntry
+    } else {
+        AppState::Menu
+    }
+}
+
+/// Start the game over, as if it had been left and entered again.
+pub fn restart_game(world: &mut World) {
+    world.run_schedule(OnExit(AppState::InGame));
+    world.run_schedule(OnEnter(AppState::InGame));
+}
+
 pub fn reset_game_state(
     mut score: ResMut<PlayerScore>,
+    mut shots_fired: ResMut<ShotsFired>,
     mut lives_remaining: ResMut<LivesRemaining>,
+    mut current_wave: ResMut<CurrentWave>,
+    mut rng: ResMut<GameRng>,
+    mut alien_timer: ResMut<AlienTimer>,
+    mut ufo_timer: ResMut<UfoTimer>,
+    mut alien_direction: ResMut<AlienDirection>,
 ) {
     score.0 = 0;
+    shots_fired.0 = 0;
     lives_remaining.0 = 3;
+    current_wave.0 = 0;
+    rng.next_game();
+
+    // Nothing from the previous game may leak into this one, or replays would diverge.
+    alien_timer.reset();
+    ufo_timer.reset();
+    *alien_direction = AlienDirection {
+        previous: EntityDirection::Left,
+        next: EntityDirection::Left,
+    };
+}
+
+pub fn toggle_pause(
+    player_input: Res<PlayerInput>,
+    current_game_state: Res<State<GameState>>,
+    mut alien_timer: ResMut<AlienTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Pause or unpause the game if the user is currently playing.
+    // The sounds follow the state, see `MixerPlugin`.
+    if player_input.pause {
+        let next_game_state = match current_game_state.get() {
+            GameState::Running => {
+                alien_timer.pause();
+                GameState::Pause
+            }
+            GameState::Pause => {
+                alien_timer.unpause();
+                GameState::Running
+            }
+            other => *other,
+        };
+        next_state.set(next_game_state);
+    }
 }
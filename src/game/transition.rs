@@ -1,9 +1,12 @@
 pub mod systems;
 
 use crate::game::aliens::systems::spawn_aliens;
+use crate::game::input::TickSet;
 use crate::game::player::systems::spawn_player;
+use crate::game::shelters::systems::spawn_shelters;
+use crate::game::shelters::Shelter;
 use crate::game::GameState;
-use crate::AppState;
+use crate::{despawn_screen, AppState};
 use bevy::prelude::*;
 use systems::*;
 
@@ -22,12 +25,22 @@ impl Plugin for TransitionPlugin {
         app.add_state::<TransitionState>()
             .add_systems(OnEnter(GameState::Transition), transition_setup)
             .add_systems(
-                Update,
+                FixedUpdate,
                 set_transition_state
+                    .in_set(TickSet::Simulation)
                     .run_if(in_state(AppState::InGame))
                     .run_if(in_state(GameState::Transition)),
             )
             .add_systems(OnEnter(TransitionState::SpawnPlayer), spawn_player)
-            .add_systems(OnEnter(TransitionState::AliensKilled), spawn_aliens);
+            .add_systems(
+                OnEnter(TransitionState::AliensKilled),
+                (
+                    next_wave,
+                    despawn_screen::<Shelter>,
+                    spawn_aliens,
+                    spawn_shelters,
+                )
+                    .chain(),
+            );
     }
 }
@@ -1,9 +1,12 @@
 use crate::game::aliens::{Alien, Ufo};
 use crate::game::lasers::Laser;
 use crate::game::player::Player;
+use crate::game::systems::finish_game;
 use crate::game::transition::TransitionState;
 use crate::game::GameState;
-use crate::resources::{BestScore, LivesRemaining, PlayerScore, TransitionTimer};
+use crate::high_scores::HighScores;
+use crate::replay::ReplayPlayback;
+use crate::resources::{BestScore, CurrentWave, LivesRemaining, PlayerScore, TransitionTimer};
 use crate::AppState;
 use bevy::prelude::*;
 
@@ -33,6 +36,8 @@ pub fn set_transition_state(
     mut best_score: ResMut<BestScore>,
     mut next_transition_state: ResMut<NextState<TransitionState>>,
     mut timer: ResMut<TransitionTimer>,
+    high_scores: Option<Res<HighScores>>,
+    playback: Option<Res<ReplayPlayback>>,
 ) {
     let mut resume_game = true;
     if timer.tick(time.delta()).finished() {
@@ -51,10 +56,16 @@ pub fn set_transition_state(
         if resume_game {
             next_game_state.set(GameState::Running);
         } else {
-            if score.0 > best_score.0 {
-                best_score.0 = score.0;
-            }
-            next_app_state.set(AppState::Menu);
+            next_app_state.set(finish_game(
+                &score,
+                &mut best_score,
+                high_scores.as_deref(),
+                playback.is_some(),
+            ));
         }
     }
 }
+
+pub fn next_wave(mut current_wave: ResMut<CurrentWave>) {
+    current_wave.0 += 1;
+}
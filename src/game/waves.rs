@@ -0,0 +1,336 @@
+use crate::config::{ConfigError, GameConfig};
+use crate::game::aliens::Alien;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::{fs, io};
+
+/// Path of the waves in the assets folder, used unless another one is given.
+pub const DEFAULT_WAVES_PATH: &str = "waves.ron";
+
+/// Aliens move 10% faster every time the whole sequence of waves has been cleared.
+const LOOP_SPEED_FACTOR: f32 = 0.9;
+
+/// A wave as written in the waves file.
+///
+/// The formation is a grid where each character is a cell: `Y`, `G` and `R` for
+/// the alien types and `.` for an empty cell. Omitted fields use the game config.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaveDefinition {
+    pub formation: Vec<String>,
+    /// Distance between the top of the window and the first line of aliens.
+    #[serde(default)]
+    pub start_height: Option<f32>,
+    #[serde(default)]
+    pub tick_duration: Option<f32>,
+    #[serde(default)]
+    pub shelters: Option<usize>,
+    #[serde(default)]
+    pub ufo_spawn_prob: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wave {
+    pub formation: Vec<Vec<Option<Alien>>>,
+    pub start_height: f32,
+    pub tick_duration: f32,
+    pub shelters: usize,
+    pub ufo_spawn_prob: f32,
+}
+
+impl Wave {
+    /// The single wave described by the alien lines of the game config.
+    pub fn from_config(config: &GameConfig) -> Self {
+        let lines = [
+            (Alien::Yellow, config.num_yellow),
+            (Alien::Green, config.num_green),
+            (Alien::Red, config.num_red),
+        ];
+        let formation = lines
+            .into_iter()
+            .flat_map(|(alien, count)| {
+                std::iter::repeat_n(vec![Some(alien); config.aliens_per_line], count)
+            })
+            .collect();
+        Self {
+            formation,
+            start_height: config.margin,
+            tick_duration: config.alien_tick_duration,
+            shelters: config.num_shelters,
+            ufo_spawn_prob: config.ufo_spawn_prob,
+        }
+    }
+
+    pub fn alien_count(&self) -> usize {
+        self.formation.iter().flatten().flatten().count()
+    }
+}
+
+impl WaveDefinition {
+    fn resolve(&self, config: &GameConfig, problems: &mut Vec<String>, number: usize) -> Wave {
+        let mut formation = Vec::with_capacity(self.formation.len());
+        for (i, line) in self.formation.iter().enumerate() {
+            let cells: Vec<_> = line
+                .chars()
+                .filter_map(|c| match c {
+                    'Y' => Some(Some(Alien::Yellow)),
+                    'G' => Some(Some(Alien::Green)),
+                    'R' => Some(Some(Alien::Red)),
+                    '.' => Some(None),
+                    _ => {
+                        problems.push(format!(
+                            "wave {number}, line {}: unknown alien `{c}`",
+                            i + 1
+                        ));
+                        None
+                    }
+                })
+                .collect();
+            if cells.len() > config.aliens_per_line {
+                problems.push(format!(
+                    "wave {number}, line {}: {} cells do not fit in `aliens_per_line` ({})",
+                    i + 1,
+                    cells.len(),
+                    config.aliens_per_line
+                ));
+            }
+            formation.push(cells);
+        }
+
+        let wave = Wave {
+            formation,
+            start_height: self.start_height.unwrap_or(config.margin),
+            tick_duration: self.tick_duration.unwrap_or(config.alien_tick_duration),
+            shelters: self.shelters.unwrap_or(config.num_shelters),
+            ufo_spawn_prob: self.ufo_spawn_prob.unwrap_or(config.ufo_spawn_prob),
+        };
+
+        if wave.alien_count() == 0 {
+            problems.push(format!("wave {number}: the formation has no alien"));
+        }
+        if wave.start_height.is_nan() || wave.start_height < 0.0 {
+            problems.push(format!(
+                "wave {number}: `start_height` must not be negative, got {}",
+                wave.start_height
+            ));
+        }
+        if wave.tick_duration.is_nan() || wave.tick_duration <= 0.0 {
+            problems.push(format!(
+                "wave {number}: `tick_duration` must be strictly positive, got {}",
+                wave.tick_duration
+            ));
+        }
+        if !(0.0..=1.0).contains(&wave.ufo_spawn_prob) {
+            problems.push(format!(
+                "wave {number}: `ufo_spawn_prob` must be between 0 and 1, got {}",
+                wave.ufo_spawn_prob
+            ));
+        }
+        let window_width = config.window_resolution().x;
+        if wave.shelters as f32 * config.shelter_size.x > window_width {
+            problems.push(format!(
+                "wave {number}: {} shelters do not fit in a {window_width} pixels wide window",
+                wave.shelters
+            ));
+        }
+
+        wave
+    }
+}
+
+/// The sequence of waves played in a game, looping once the last one is cleared.
+#[derive(Resource, Clone, Debug)]
+pub struct Waves(pub Vec<Wave>);
+
+impl Waves {
+    /// Load the waves stored at `path`, or a single wave built from the config if
+    /// there is no such file.
+    pub fn load(path: impl AsRef<Path>, config: &GameConfig) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::from_ron(&contents, config),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Ok(Self(vec![Wave::from_config(config)]))
+            }
+            Err(err) => Err(ConfigError::Io(err)),
+        }
+    }
+
+    pub fn from_ron(contents: &str, config: &GameConfig) -> Result<Self, ConfigError> {
+        let definitions: Vec<WaveDefinition> = ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(contents)
+            .map_err(ConfigError::Parse)?;
+
+        let mut problems = Vec::new();
+        if definitions.is_empty() {
+            problems.push("at least one wave is required".to_string());
+        }
+        let waves = definitions
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| definition.resolve(config, &mut problems, i + 1))
+            .collect();
+
+        if problems.is_empty() {
+            Ok(Self(waves))
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// The wave played at `index`, counting from zero since the start of the game.
+    pub fn get(&self, index: usize) -> &Wave {
+        &self.0[index % self.0.len()]
+    }
+
+    /// The initial duration of an alien tick for the wave played at `index`.
+    pub fn tick_duration(&self, index: usize) -> f32 {
+        let loops = (index / self.0.len()) as i32;
+        self.get(index).tick_duration * LOOP_SPEED_FACTOR.powi(loops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::aliens::systems::spawn_aliens;
+    use crate::game::aliens::{AlienSprites, RippleMarch};
+    use crate::game::transition::systems::next_wave;
+    use crate::resources::{AlienTimer, CurrentWave, PlayField};
+    use crate::user_settings::UserSettings;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn problems(contents: &str) -> Vec<String> {
+        match Waves::from_ron(contents, &GameConfig::default()) {
+            Err(ConfigError::Invalid(problems)) => problems,
+            other => panic!("expected invalid waves, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_formation_and_fallbacks() {
+        let config = GameConfig::default();
+        let waves = Waves::from_ron(
+            r#"[(formation: ["Y.G", "RRR"], tick_duration: 0.5)]"#,
+            &config,
+        )
+        .unwrap();
+        let wave = waves.get(0);
+        assert_eq!(
+            wave.formation,
+            [
+                vec![Some(Alien::Yellow), None, Some(Alien::Green)],
+                vec![Some(Alien::Red); 3],
+            ]
+        );
+        assert_eq!(wave.alien_count(), 5);
+        assert_eq!(wave.tick_duration, 0.5);
+        assert_eq!(wave.shelters, config.num_shelters);
+        assert_eq!(wave.ufo_spawn_prob, config.ufo_spawn_prob);
+        assert_eq!(wave.start_height, config.margin);
+    }
+
+    #[test]
+    fn rejects_unknown_cell() {
+        let problems = problems(r#"[(formation: ["YXY"])]"#);
+        assert_eq!(problems, ["wave 1, line 1: unknown alien `X`"]);
+    }
+
+    #[test]
+    fn rejects_line_wider_than_aliens_per_line() {
+        let config = GameConfig::default();
+        let wide = "Y".repeat(config.aliens_per_line + 1);
+        let problems = problems(&format!(r#"[(formation: ["YYY", "{wide}"])]"#));
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("wave 1, line 2:"), "{problems:?}");
+    }
+
+    #[test]
+    fn shorter_lines_are_left_aligned() {
+        let waves =
+            Waves::from_ron(r#"[(formation: ["YYYYY", "GG"])]"#, &GameConfig::default()).unwrap();
+        let formation = &waves.get(0).formation;
+        assert_eq!(formation[0].len(), 5);
+        assert_eq!(formation[1], [Some(Alien::Green), Some(Alien::Green)]);
+    }
+
+    #[test]
+    fn rejects_empty_formation() {
+        assert_eq!(
+            problems(r#"[(formation: [])]"#),
+            ["wave 1: the formation has no alien"]
+        );
+        assert_eq!(
+            problems(r#"[(formation: ["YY"]), (formation: ["...", ""])]"#),
+            ["wave 2: the formation has no alien"]
+        );
+    }
+
+    #[test]
+    fn rejects_empty_wave_list() {
+        assert_eq!(problems("[]"), ["at least one wave is required"]);
+    }
+
+    #[test]
+    fn rejects_unknown_field() {
+        let result = Waves::from_ron(
+            r#"[(formation: ["Y"], speed: 2.0)]"#,
+            &GameConfig::default(),
+        );
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn waves_loop_faster() {
+        let waves = Waves::from_ron(
+            r#"[(formation: ["Y"], tick_duration: 1.0), (formation: ["G"], tick_duration: 0.5)]"#,
+            &GameConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(waves.get(3).formation, [[Some(Alien::Green)]]);
+        assert_eq!(waves.tick_duration(1), 0.5);
+        assert_eq!(waves.tick_duration(2), LOOP_SPEED_FACTOR);
+    }
+
+    #[test]
+    fn aliens_killed_spawns_the_next_wave() {
+        let config = GameConfig::default();
+        let waves = Waves::from_ron(
+            r#"[
+                (formation: ["YYY"], tick_duration: 1.0),
+                (formation: ["GG", "RR"], tick_duration: 0.5),
+            ]"#,
+            &config,
+        )
+        .unwrap();
+
+        let mut world = World::new();
+        world.insert_resource(PlayField(config.window_resolution()));
+        world.insert_resource(config);
+        world.insert_resource(waves);
+        world.insert_resource(CurrentWave(0));
+        world.insert_resource(UserSettings::default());
+        world.insert_resource(AlienTimer(Timer::from_seconds(1.0, TimerMode::Repeating)));
+        world.insert_resource(RippleMarch::default());
+        world.insert_resource(AlienSprites {
+            yellow: default(),
+            green: default(),
+            red: default(),
+            explosion: default(),
+            projectiles: default(),
+        });
+
+        // The systems run when the last alien of a wave dies.
+        world.run_system_once(next_wave);
+        world.run_system_once(spawn_aliens);
+
+        assert_eq!(world.resource::<CurrentWave>().0, 1);
+        let mut aliens: Vec<_> = world.query::<&Alien>().iter(&world).cloned().collect();
+        aliens.sort_by_key(|alien| format!("{alien:?}"));
+        assert_eq!(aliens, [Alien::Green, Alien::Green, Alien::Red, Alien::Red]);
+        let tick = world.resource::<AlienTimer>().duration().as_secs_f32();
+        assert_eq!(tick, 0.5 * UserSettings::default().difficulty.tick_factor());
+    }
+}
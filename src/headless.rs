@@ -0,0 +1,109 @@
+use crate::game::GameState;
+use crate::resources::{CurrentWave, GameRng, PlayerScore};
+use crate::user_settings::UserSettings;
+use crate::AppState;
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// Simulated time between two frames, so that every frame runs exactly one fixed update.
+pub const SIMULATION_STEP: Duration = Duration::from_micros(15_625);
+
+pub struct GameResult {
+    pub score: u32,
+    pub wave: usize,
+    pub seed: u64,
+}
+
+/// Games played so far by the simulation.
+#[derive(Resource, Default)]
+pub struct Simulation {
+    pub games: usize,
+    pub results: Vec<GameResult>,
+}
+
+/// Run `games` games back to back without a window or an audio device, as fast
+/// as possible, then exit.
+pub struct HeadlessPlugin {
+    pub games: usize,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)))
+            .add_plugins((
+                AssetPlugin::default(),
+                InputPlugin,
+                HierarchyPlugin,
+                TransformPlugin,
+            ))
+            // Assets are never rendered nor played, but gameplay systems still hold handles to them.
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlas>()
+            .init_asset::<Font>()
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(SIMULATION_STEP))
+            // Simulations ignore the settings of the player and play on normal, or on the
+            // difficulty of the replay being played back.
+            .init_resource::<UserSettings>()
+            .insert_resource(Simulation {
+                games: self.games,
+                ..default()
+            })
+            .add_systems(Update, next_simulated_game.run_if(in_state(AppState::Menu)))
+            .add_systems(OnExit(AppState::InGame), record_simulated_game);
+    }
+}
+
+pub fn next_simulated_game(
+    simulation: Res<Simulation>,
+    mut app_exit_event_writer: EventWriter<AppExit>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if simulation.results.len() < simulation.games {
+        next_app_state.set(AppState::InGame);
+        next_game_state.set(GameState::Running);
+    } else {
+        print_summary(&simulation.results);
+        app_exit_event_writer.send(AppExit);
+    }
+}
+
+pub fn record_simulated_game(
+    mut simulation: ResMut<Simulation>,
+    score: Res<PlayerScore>,
+    current_wave: Res<CurrentWave>,
+    rng: Res<GameRng>,
+) {
+    let result = GameResult {
+        score: score.0,
+        wave: current_wave.0 + 1,
+        seed: rng.seed(),
+    };
+    println!(
+        "game {}: score {}, wave {}, seed {}",
+        simulation.results.len() + 1,
+        result.score,
+        result.wave,
+        result.seed
+    );
+    simulation.results.push(result);
+}
+
+fn print_summary(results: &[GameResult]) {
+    if results.is_empty() {
+        return;
+    }
+    let games = results.len() as f32;
+    let mean_score = results.iter().map(|r| r.score as f32).sum::<f32>() / games;
+    let mean_wave = results.iter().map(|r| r.wave as f32).sum::<f32>() / games;
+    let best_score = results.iter().map(|r| r.score).max().unwrap_or_default();
+    println!(
+        "{} games: mean score {mean_score:.1}, best score {best_score}, mean wave {mean_wave:.2}",
+        results.len()
+    );
+}
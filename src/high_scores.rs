@@ -0,0 +1,256 @@
+use crate::resources::BestScore;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const FILE_NAME: &str = "high_scores.ron";
+
+/// Number of letters of a player name.
+pub const NAME_LENGTH: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    /// Wave reached, counting from one.
+    pub wave: usize,
+    /// Day the game was played, formatted as `YYYY-MM-DD`.
+    pub date: String,
+    pub seed: u64,
+}
+
+/// The best scores ever made, from the highest to the lowest.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    pub const CAPACITY: usize = 10;
+
+    /// Default location of the table, in the data directory of the user.
+    pub fn default_path() -> Option<PathBuf> {
+        storage::data_path(FILE_NAME)
+    }
+
+    /// Load the table stored at `path`, or an empty table if it is missing or corrupted.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let mut high_scores: Self = storage::load(path.as_ref(), "high scores");
+        high_scores.sort();
+        high_scores
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        storage::save(self, path.as_ref())
+    }
+
+    /// Whether `score` would enter the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.0.len() < Self::CAPACITY || self.0.iter().any(|entry| score > entry.score))
+    }
+
+    /// Insert `entry` in the table and return its rank, counting from zero, if it
+    /// made it into the table.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        // Older scores stay ahead of new ones with the same value.
+        let rank = self.0.partition_point(|other| other.score >= entry.score);
+        self.0.insert(rank, entry);
+        self.0.truncate(Self::CAPACITY);
+        Some(rank)
+    }
+
+    /// Insert `entry` in the table and write it to `path`, if it made it into the table.
+    pub fn record(&mut self, entry: HighScore, path: &HighScoresPath) {
+        if self.insert(entry).is_none() {
+            return;
+        }
+        if let Some(path) = &path.0 {
+            if let Err(err) = self.save(path) {
+                error!(
+                    "Failed to save the high scores to {}: {err}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    pub fn best(&self) -> u32 {
+        self.0.first().map(|entry| entry.score).unwrap_or_default()
+    }
+
+    fn sort(&mut self) {
+        self.0.sort_by_key(|entry| Reverse(entry.score));
+        self.0.truncate(Self::CAPACITY);
+    }
+}
+
+/// Where the high scores are saved, if anywhere.
+#[derive(Resource)]
+pub struct HighScoresPath(pub Option<PathBuf>);
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let path = HighScores::default_path();
+        let high_scores = path.as_ref().map(HighScores::load).unwrap_or_default();
+
+        app.insert_resource(high_scores)
+            .insert_resource(HighScoresPath(path))
+            .add_systems(Startup, load_best_score.after(crate::add_resources));
+    }
+}
+
+pub fn load_best_score(high_scores: Res<HighScores>, mut best_score: ResMut<BestScore>) {
+    best_score.0 = best_score.0.max(high_scores.best());
+}
+
+/// The current date in UTC, formatted as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Convert a number of days since 1970-01-01 to a date of the Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            wave: 1,
+            date: "2024-01-01".to_string(),
+            seed: 0,
+        }
+    }
+
+    /// A full table, from 1000 down to 100.
+    fn full_table() -> HighScores {
+        HighScores(
+            (1..=HighScores::CAPACITY as u32)
+                .rev()
+                .map(|rank| entry("AAA", rank * 100))
+                .collect(),
+        )
+    }
+
+    fn names(high_scores: &HighScores) -> Vec<&str> {
+        high_scores
+            .0
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn qualifies_until_the_table_is_full() {
+        let mut high_scores = HighScores::default();
+        assert!(high_scores.qualifies(1));
+        assert!(!high_scores.qualifies(0));
+
+        high_scores.0.push(entry("AAA", 500));
+        assert!(high_scores.qualifies(10));
+    }
+
+    #[test]
+    fn qualifies_past_the_lowest_score_of_a_full_table() {
+        let high_scores = full_table();
+        assert!(high_scores.qualifies(101));
+        assert!(high_scores.qualifies(5000));
+        assert!(!high_scores.qualifies(100));
+        assert!(!high_scores.qualifies(50));
+    }
+
+    #[test]
+    fn insert_keeps_older_scores_ahead_on_ties() {
+        let mut high_scores = HighScores(vec![entry("OLD", 300), entry("LOW", 100)]);
+        assert_eq!(high_scores.insert(entry("NEW", 300)), Some(1));
+        assert_eq!(high_scores.insert(entry("TOP", 400)), Some(0));
+        assert_eq!(names(&high_scores), ["TOP", "OLD", "NEW", "LOW"]);
+    }
+
+    #[test]
+    fn insert_cuts_the_table_to_its_capacity() {
+        let mut high_scores = full_table();
+        assert_eq!(high_scores.insert(entry("NEW", 550)), Some(5));
+        assert_eq!(high_scores.0.len(), HighScores::CAPACITY);
+        assert_eq!(high_scores.0.last().unwrap().score, 200);
+        assert_eq!(high_scores.0[5].name, "NEW");
+
+        assert_eq!(high_scores.insert(entry("TIE", 200)), None);
+        assert_eq!(high_scores.0.len(), HighScores::CAPACITY);
+        assert!(!names(&high_scores).contains(&"TIE"));
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        // Centuries are not leap years, unless they are divisible by 400.
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn load_moves_a_corrupted_file_aside() {
+        let dir = std::env::temp_dir().join(format!("invaders-scores-{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "[(name: \"AAA\", score: ").unwrap();
+
+        let high_scores = HighScores::load(&path);
+        let moved = !path.exists() && path.with_extension("ron.corrupted").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(high_scores, HighScores::default());
+        assert!(moved);
+    }
+
+    #[test]
+    fn load_sorts_and_cuts_the_table() {
+        let dir = std::env::temp_dir().join(format!("invaders-sorted-{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        let mut unsorted = full_table();
+        unsorted.0.reverse();
+        unsorted.0.push(entry("TOP", 2000));
+        unsorted.save(&path).unwrap();
+
+        let high_scores = HighScores::load(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(high_scores.0.len(), HighScores::CAPACITY);
+        assert_eq!(high_scores.0[0].name, "TOP");
+        assert_eq!(high_scores.0.last().unwrap().score, 200);
+    }
+}
@@ -1,16 +1,26 @@
+pub mod audio;
+pub mod cli;
+pub mod config;
 pub mod game;
+pub mod headless;
+pub mod high_scores;
+pub mod replay;
 pub mod resources;
 pub mod settings;
+pub mod storage;
 pub mod ui;
+pub mod user_settings;
 
-use crate::game::{EntityDirection, GameState};
+use crate::audio::AudioMixer;
+use crate::config::GameConfig;
+use crate::game::input::{Action, ActiveGamepad};
+use crate::game::EntityDirection;
 use crate::resources::*;
 use crate::settings::*;
 use bevy::app::AppExit;
-use bevy::audio::{PlaybackMode, Volume, VolumeLevel};
 use bevy::core::FrameCount;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
+use bevy::render::camera::ScalingMode;
 use bevy::winit::WinitWindows;
 use std::time::Duration;
 use winit::window::Icon;
@@ -21,30 +31,20 @@ pub enum AppState {
     Menu,
     Pause,
     InGame,
+    NameEntry,
 }
 
 #[derive(Component)]
 pub struct MainMusic;
 
-pub fn get_window_resolution() -> Vec2 {
-    let width = 2.0 * MARGIN
-        + ALIENS_PER_LINE as f32 * ALIEN_SIZE.x
-        + (ALIENS_PER_LINE - 1) as f32 * SPACE_BETWEEN_ALIENS.x;
-    let height = 600.0;
-    Vec2::new(width, height)
-}
-
 pub fn despawn_screen<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
     for entity in &entities {
         commands.entity(entity).despawn_recursive();
     }
 }
 
-pub fn add_resources(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(ClearColor(BACKGROUND_COLOR));
-
-    commands.insert_resource(ButtonHoveredSound(asset_server.load("audio/hovered.ogg")));
-    commands.insert_resource(ButtonPressedSound(asset_server.load("audio/pressed.ogg")));
+pub fn add_resources(mut commands: Commands, config: Res<GameConfig>) {
+    commands.insert_resource(ClearColor(config.background_color));
 
     commands.insert_resource(AlreadyPlayed(false));
 
@@ -53,32 +53,15 @@ pub fn add_resources(mut commands: Commands, asset_server: Res<AssetServer>) {
         TimerMode::Repeating,
     )));
 
-    let shoot = asset_server.load("audio/shoot.ogg");
-    commands.insert_resource(ShootSound(shoot));
-    let explosion = asset_server.load("audio/explosion.ogg");
-    commands.insert_resource(ExplosionSound(explosion));
-    let invader_killed = asset_server.load("audio/invaderkilled.ogg");
-    commands.insert_resource(InvaderKilledSound(invader_killed));
-
-    let invader_1 = asset_server.load("audio/fastinvader1.ogg");
-    let invader_2 = asset_server.load("audio/fastinvader2.ogg");
-    let invader_3 = asset_server.load("audio/fastinvader3.ogg");
-    let invader_4 = asset_server.load("audio/fastinvader4.ogg");
-    commands.insert_resource(AlienSounds(InvadersMovingSound::new(
-        0,
-        [invader_1, invader_2, invader_3, invader_4],
-    )));
-
     commands.insert_resource(PlayerScore(0));
+    commands.insert_resource(ShotsFired(0));
     commands.insert_resource(BestScore(0));
 
     commands.insert_resource(AlienTimer(Timer::from_seconds(
-        ALIEN_TICK_DURATION,
+        config.alien_tick_duration,
         TimerMode::Repeating,
     )));
-    commands.insert_resource(AlienTimerDuration(Duration::from_secs_f32(
-        ALIEN_TICK_DURATION,
-    )));
+    commands.insert_resource(CurrentWave(0));
     commands.insert_resource(UfoTimer(Timer::from_seconds(1.0, TimerMode::Repeating)));
 
     commands.insert_resource(AlienDirection {
@@ -109,67 +92,47 @@ pub fn set_window_icon(windows: NonSend<WinitWindows>) {
 pub fn play_main_music(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
     frames: Res<FrameCount>,
 ) {
     if frames.0 == WINDOW_VISIBLE_DELAY {
-        let music = asset_server.load("audio/music.ogg");
-        commands.spawn((
-            AudioBundle {
-                source: music,
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Loop,
-                    volume: Volume::Relative(VolumeLevel::new(0.5)),
-                    ..default()
-                },
-            },
-            MainMusic,
-        ));
+        let music: Handle<AudioSource> = asset_server.load("audio/music.ogg");
+        commands.spawn((music, mixer.music(PlaybackSettings::LOOP), MainMusic));
     }
 }
 
-pub fn spawn_camera(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
-    let window = window_query.get_single().unwrap();
-    commands.spawn(Camera2dBundle {
-        transform: Transform::from_xyz(window.width() / 2.0, window.height() / 2.0, 0.0),
+pub fn spawn_camera(mut commands: Commands, play_field: Res<PlayField>) {
+    let mut camera = Camera2dBundle {
+        transform: Transform::from_xyz(play_field.width() / 2.0, play_field.height() / 2.0, 0.0),
         ..default()
-    });
+    };
+    // Keep the whole play field in view when the window is larger, e.g. in fullscreen.
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: play_field.width(),
+        min_height: play_field.height(),
+    };
+    // The ears sit on the sides of the play field, so sounds pan with their position.
+    commands.spawn((camera, SpatialListener::new(play_field.width())));
 }
 
 pub fn handle_input(
     mut app_exit_event_writer: EventWriter<AppExit>,
-    sinks_query: Query<&AudioSink>,
     keyboard_input: Res<Input<KeyCode>>,
-    current_app_state: Res<State<AppState>>,
-    current_game_state: Res<State<GameState>>,
-    mut alien_timer: ResMut<AlienTimer>,
-    mut next_state: ResMut<NextState<GameState>>,
+    button_input: Res<Input<GamepadButton>>,
+    active_gamepad: Res<ActiveGamepad>,
+    config: Res<GameConfig>,
 ) {
-    if let AppState::InGame = current_app_state.get() {
-        // Pause or unpause the game if the user is currently playing.
-        if keyboard_input.just_pressed(KeyCode::P) {
-            let (next_game_state, toggle) = match current_game_state.get() {
-                GameState::Running => {
-                    alien_timer.pause();
-                    (GameState::Pause, true)
-                }
-                GameState::Pause => {
-                    alien_timer.unpause();
-                    (GameState::Running, true)
-                }
-                other => (*other, false),
-            };
-            next_state.set(next_game_state);
-            if toggle {
-                for sink in sinks_query.iter() {
-                    // Toggle all sounds.
-                    sink.toggle();
-                }
-            }
-        }
-    }
-
+    let quit_button = active_gamepad.0.is_some_and(|gamepad| {
+        config
+            .gamepad_bindings
+            .just_pressed(Action::Quit, gamepad, &button_input)
+    });
     // Quit the app.
-    if keyboard_input.just_pressed(KeyCode::Q) {
+    if config
+        .key_bindings
+        .just_pressed(Action::Quit, &keyboard_input)
+        || quit_button
+    {
         app_exit_event_writer.send(AppExit);
     }
 }
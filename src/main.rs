@@ -1,34 +1,115 @@
 #![windows_subsystem = "windows"]
 
+use bevy::audio::{AudioPlugin, SpatialScale};
 use bevy::prelude::*;
-use bevy::window::close_on_esc;
+use invaders::audio::MixerPlugin;
+use invaders::cli::{Args, USAGE};
+use invaders::config::GameConfig;
+use invaders::game::input::systems::{buffer_player_input, pause_on_focus_loss};
+use invaders::game::input::InputSource;
 use invaders::game::systems::*;
+use invaders::game::waves::Waves;
 use invaders::game::*;
+use invaders::headless::HeadlessPlugin;
+use invaders::high_scores::HighScoresPlugin;
+use invaders::replay::{Replay, ReplayPlugin};
+use invaders::resources::{GameRng, PlayField};
 use invaders::ui::*;
+use invaders::user_settings::UserSettingsPlugin;
 use invaders::*;
+use std::process::exit;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Invaders".into(),
-                resolution: get_window_resolution().into(),
-                enabled_buttons: bevy::window::EnabledButtons {
-                    maximize: false,
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("error: {err}\n{USAGE}");
+        exit(2);
+    });
+    let config = GameConfig::load(&args.config).unwrap_or_else(|err| {
+        eprintln!(
+            "error: failed to load the configuration from {}: {err}",
+            args.config.display()
+        );
+        exit(1);
+    });
+    for (key, reason) in config.ignored_keys() {
+        eprintln!("warning: `{key}` is no longer used, {reason}");
+    }
+    let waves = Waves::load(&args.waves, &config).unwrap_or_else(|err| {
+        eprintln!(
+            "error: failed to load the waves from {}: {err}",
+            args.waves.display()
+        );
+        exit(1);
+    });
+
+    let replay = args.replay.as_ref().map(|path| {
+        Replay::load(path).unwrap_or_else(|err| {
+            eprintln!(
+                "error: failed to load the replay from {}: {err}",
+                path.display()
+            );
+            exit(1);
+        })
+    });
+    let seed = replay.as_ref().map(|replay| replay.seed);
+
+    let mut app = App::new();
+
+    if let Some(games) = args.headless {
+        app.add_plugins(HeadlessPlugin { games });
+    } else {
+        // Scale the play field down to a unit width for spatial audio, so that sounds
+        // are panned but not attenuated by their distance to the listener.
+        let spatial_scale = SpatialScale::new_2d(1.0 / config.window_resolution().x);
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Invaders".into(),
+                        resolution: config.window_resolution().into(),
+                        enabled_buttons: bevy::window::EnabledButtons {
+                            maximize: false,
+                            ..default()
+                        },
+                        visible: false,
+                        ..default()
+                    }),
                     ..default()
-                },
-                visible: false,
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(UiPlugin)
-        .add_plugins(GamePlugin)
-        .add_state::<AppState>()
-        .add_systems(Startup, (set_window_icon, spawn_camera, add_resources))
+                })
+                .set(AudioPlugin {
+                    spatial_scale,
+                    ..default()
+                }),
+        )
+        .add_plugins((UiPlugin, HighScoresPlugin, UserSettingsPlugin, MixerPlugin))
+        .add_systems(Startup, (set_window_icon, spawn_camera))
         .add_systems(
-            Update,
-            (make_visible, play_main_music, handle_input, close_on_esc),
+            PreUpdate,
+            pause_on_focus_loss
+                .after(buffer_player_input)
+                .run_if(resource_equals(InputSource::Live)),
         )
+        .add_systems(
+            Update,
+            (
+                make_visible,
+                play_main_music,
+                // Letters are typed on the name entry screen.
+                handle_input.run_if(not(in_state(AppState::NameEntry))),
+            ),
+        );
+    }
+
+    app.insert_resource(PlayField(config.window_resolution()))
+        .insert_resource(GameRng::new(seed.or(args.seed).or(config.seed)))
+        .insert_resource(config)
+        .insert_resource(waves)
+        .add_plugins(GamePlugin)
+        .add_plugins(ReplayPlugin {
+            record: args.record,
+            playback: replay,
+        })
+        .add_state::<AppState>()
+        .add_systems(Startup, add_resources)
         .run();
 }
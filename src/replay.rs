@@ -0,0 +1,453 @@
+use crate::game::input::systems::read_buffered_input;
+use crate::game::input::{InputSource, PlayerInput, TickSet};
+use crate::game::systems::reset_game_state;
+use crate::game::{GameOver, GameState};
+use crate::resources::GameRng;
+use crate::user_settings::{Difficulty, UserSettings};
+use crate::AppState;
+use bevy::prelude::*;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+const MAGIC: &[u8; 4] = b"INVR";
+const VERSION: u8 = 2;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 8 + 1;
+const RUN_LEN: usize = 4 + 1;
+/// Longest replay that can be read, four hours of play at the default 64 ticks per
+/// second, so that a corrupted file cannot make the game run out of memory.
+const MAX_TICKS: usize = 4 * 60 * 60 * 64;
+
+/// The seed and difficulty of a game and the input of the player for each of its fixed
+/// update ticks.
+///
+/// Replays only reproduce a game when played with the same configuration and waves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub inputs: Vec<PlayerInput>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    InvalidFormat(&'static str),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not read the file: {err}"),
+            ReplayError::InvalidFormat(reason) => write!(f, "not a valid replay: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    /// Encode the replay as a header followed by runs of identical inputs, each
+    /// stored as a little-endian `u32` length and the input bits.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(match self.difficulty {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+        });
+
+        let mut inputs = self.inputs.iter().map(|input| input.to_bits()).peekable();
+        while let Some(bits) = inputs.next() {
+            let mut length = 1_u32;
+            while length < u32::MAX && inputs.next_if_eq(&bits).is_some() {
+                length += 1;
+            }
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.push(bits);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ReplayError::InvalidFormat("missing header"));
+        }
+        if bytes[MAGIC.len()] != VERSION {
+            return Err(ReplayError::InvalidFormat("unsupported version"));
+        }
+        let seed = u64::from_le_bytes(bytes[MAGIC.len() + 1..HEADER_LEN - 1].try_into().unwrap());
+        let difficulty = match bytes[HEADER_LEN - 1] {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Normal,
+            2 => Difficulty::Hard,
+            _ => return Err(ReplayError::InvalidFormat("unknown difficulty")),
+        };
+
+        let runs = &bytes[HEADER_LEN..];
+        if !runs.len().is_multiple_of(RUN_LEN) {
+            return Err(ReplayError::InvalidFormat("truncated input"));
+        }
+        let mut inputs = Vec::new();
+        for run in runs.chunks_exact(RUN_LEN) {
+            let length = u32::from_le_bytes(run[..4].try_into().unwrap()) as usize;
+            if length == 0 {
+                return Err(ReplayError::InvalidFormat("empty run of input"));
+            }
+            if length > MAX_TICKS - inputs.len() {
+                return Err(ReplayError::InvalidFormat("too long"));
+            }
+            let input = PlayerInput::from_bits(run[4]);
+            inputs.extend(std::iter::repeat_n(input, length));
+        }
+
+        Ok(Self {
+            seed,
+            difficulty,
+            inputs,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let bytes = fs::read(path).map_err(ReplayError::Io)?;
+        Self::decode(&bytes)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.encode())
+    }
+}
+
+/// Records the game being played, and writes it to `path` once it is over.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+    /// Whether the recorded game is over. Games quit or restarted from the pause menu
+    /// are not saved, so that they do not overwrite the last complete replay.
+    pub game_over: bool,
+}
+
+/// Feeds the input of a replay to the game instead of the keyboard and gamepad.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub tick: usize,
+    /// Difficulty chosen by the player, given back once the replayed game is over.
+    pub player_difficulty: Difficulty,
+}
+
+pub struct ReplayPlugin {
+    /// Where to record each game, if anywhere.
+    pub record: Option<PathBuf>,
+    /// Replay to play back as soon as the app starts.
+    pub playback: Option<Replay>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = &self.record {
+            app.insert_resource(ReplayRecorder {
+                path: path.clone(),
+                replay: Replay::default(),
+                game_over: false,
+            })
+            .add_systems(
+                OnEnter(AppState::InGame),
+                start_recording.after(reset_game_state),
+            )
+            .add_systems(
+                FixedUpdate,
+                record_input
+                    .in_set(TickSet::Input)
+                    .after(read_buffered_input)
+                    .after(play_back_input)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                finish_recording
+                    .in_set(TickSet::Simulation)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), save_recording);
+        }
+
+        if let Some(replay) = &self.playback {
+            app.insert_resource(ReplayPlayback {
+                replay: replay.clone(),
+                tick: 0,
+                player_difficulty: Difficulty::default(),
+            })
+            .add_systems(Startup, start_playback)
+            .add_systems(
+                OnExit(AppState::InGame),
+                finish_playback.run_if(resource_exists::<ReplayPlayback>()),
+            );
+        }
+
+        app.add_systems(
+            FixedUpdate,
+            play_back_input
+                .in_set(TickSet::Input)
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_exists::<ReplayPlayback>()),
+        );
+    }
+}
+
+pub fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    settings: Res<UserSettings>,
+) {
+    recorder.replay = Replay {
+        seed: rng.seed(),
+        difficulty: settings.difficulty,
+        inputs: Vec::new(),
+    };
+    recorder.game_over = false;
+}
+
+pub fn record_input(mut recorder: ResMut<ReplayRecorder>, player_input: Res<PlayerInput>) {
+    // Longer replays could not be read back.
+    if recorder.replay.inputs.len() < MAX_TICKS {
+        recorder.replay.inputs.push(*player_input);
+    }
+}
+
+pub fn finish_recording(
+    mut game_over_event_reader: EventReader<GameOver>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if game_over_event_reader.read().next().is_some() {
+        recorder.game_over = true;
+    }
+}
+
+pub fn save_recording(recorder: Res<ReplayRecorder>) {
+    if !recorder.game_over {
+        return;
+    }
+    if let Err(err) = recorder.replay.save(&recorder.path) {
+        error!(
+            "Failed to save the replay to {}: {err}",
+            recorder.path.display()
+        );
+    }
+}
+
+/// Play the replay on its own difficulty, whatever the settings of the player.
+pub fn start_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    mut settings: ResMut<UserSettings>,
+    mut input_source: ResMut<InputSource>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    playback.player_difficulty =
+        std::mem::replace(&mut settings.difficulty, playback.replay.difficulty);
+    *input_source = InputSource::Replay;
+    next_app_state.set(AppState::InGame);
+    next_game_state.set(GameState::Running);
+}
+
+pub fn play_back_input(
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    // Once the recorded input runs out the player stops doing anything.
+    *player_input = playback
+        .replay
+        .inputs
+        .get(playback.tick)
+        .copied()
+        .unwrap_or_default();
+    playback.tick += 1;
+}
+
+/// Give the control back to the player once the replayed game is over.
+pub fn finish_playback(
+    mut commands: Commands,
+    playback: Res<ReplayPlayback>,
+    mut settings: ResMut<UserSettings>,
+    mut input_source: ResMut<InputSource>,
+) {
+    settings.difficulty = playback.player_difficulty;
+    commands.remove_resource::<ReplayPlayback>();
+    *input_source = InputSource::Live;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(left: bool, fire: bool) -> PlayerInput {
+        PlayerInput {
+            left,
+            fire,
+            ..default()
+        }
+    }
+
+    fn header(seed: u64) -> Vec<u8> {
+        Replay { seed, ..default() }.encode()
+    }
+
+    fn run(length: u32, bits: u8) -> Vec<u8> {
+        let mut bytes = length.to_le_bytes().to_vec();
+        bytes.push(bits);
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = Replay {
+            seed: 42,
+            difficulty: Difficulty::Hard,
+            inputs: vec![
+                input(false, false),
+                input(true, false),
+                input(true, false),
+                input(true, true),
+                PlayerInput {
+                    right: true,
+                    pause: true,
+                    ..default()
+                },
+            ],
+        };
+        let bytes = replay.encode();
+        assert_eq!(bytes.len(), HEADER_LEN + 4 * RUN_LEN);
+        assert_eq!(Replay::decode(&bytes).unwrap(), replay);
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let replay = Replay {
+            seed: u64::MAX,
+            difficulty: Difficulty::Easy,
+            inputs: Vec::new(),
+        };
+        assert_eq!(Replay::decode(&replay.encode()).unwrap(), replay);
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        let mut bytes = header(1);
+        bytes[0] = b'X';
+        assert!(Replay::decode(&bytes).is_err());
+
+        let mut bytes = header(1);
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert!(Replay::decode(&bytes).is_err());
+
+        let mut bytes = header(1);
+        bytes[HEADER_LEN - 1] = 3;
+        assert!(Replay::decode(&bytes).is_err());
+
+        assert!(Replay::decode(&header(1)[..HEADER_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_run() {
+        let mut bytes = header(1);
+        bytes.extend(run(3, 0));
+        bytes.pop();
+        assert!(Replay::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_run() {
+        let mut bytes = header(1);
+        bytes.extend(run(0, 1));
+        assert!(Replay::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_too_many_ticks() {
+        let mut bytes = header(1);
+        bytes.extend(run(u32::MAX, 1));
+        assert!(Replay::decode(&bytes).is_err());
+
+        // Runs that are each short enough must not add up past the limit either.
+        let mut bytes = header(1);
+        bytes.extend(run(MAX_TICKS as u32, 1));
+        bytes.extend(run(1, 0));
+        assert!(Replay::decode(&bytes).is_err());
+
+        let mut bytes = header(1);
+        bytes.extend(run(MAX_TICKS as u32, 1));
+        assert_eq!(Replay::decode(&bytes).unwrap().inputs.len(), MAX_TICKS);
+    }
+
+    #[test]
+    fn playback_uses_the_difficulty_of_the_replay() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.insert_resource(ReplayPlayback {
+            replay: Replay {
+                difficulty: Difficulty::Hard,
+                ..default()
+            },
+            tick: 0,
+            player_difficulty: Difficulty::default(),
+        });
+        world.insert_resource(UserSettings {
+            difficulty: Difficulty::Easy,
+            ..default()
+        });
+        world.init_resource::<InputSource>();
+        world.init_resource::<NextState<AppState>>();
+        world.init_resource::<NextState<GameState>>();
+
+        world.run_system_once(start_playback);
+        assert_eq!(
+            world.resource::<UserSettings>().difficulty,
+            Difficulty::Hard
+        );
+        assert_eq!(*world.resource::<InputSource>(), InputSource::Replay);
+
+        world.run_system_once(finish_playback);
+        assert_eq!(
+            world.resource::<UserSettings>().difficulty,
+            Difficulty::Easy
+        );
+        assert_eq!(*world.resource::<InputSource>(), InputSource::Live);
+        assert!(!world.contains_resource::<ReplayPlayback>());
+    }
+
+    #[test]
+    fn only_finished_games_are_saved() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let path = std::env::temp_dir().join(format!("invaders-replay-{}", std::process::id()));
+        let mut world = World::new();
+        world.init_resource::<Events<GameOver>>();
+        world.insert_resource(ReplayRecorder {
+            path: path.clone(),
+            replay: Replay {
+                seed: 7,
+                inputs: vec![input(true, false)],
+                ..default()
+            },
+            game_over: false,
+        });
+
+        // Quitting from the pause menu.
+        world.run_system_once(finish_recording);
+        world.run_system_once(save_recording);
+        assert!(!path.exists());
+
+        world.send_event(GameOver);
+        world.run_system_once(finish_recording);
+        world.run_system_once(save_recording);
+        let saved = Replay::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved.unwrap(), world.resource::<ReplayRecorder>().replay);
+    }
+}
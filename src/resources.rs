@@ -1,29 +1,76 @@
 use crate::game::EntityDirection;
 use bevy::prelude::*;
-use std::time::Duration;
+use rand::{random, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::ops::{Deref, DerefMut};
+
+/// Dimensions of the area where the game takes place, which match the window
+/// when there is one.
+#[derive(Resource, Clone, Copy)]
+pub struct PlayField(pub Vec2);
+
+impl PlayField {
+    pub fn width(&self) -> f32 {
+        self.0.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.0.y
+    }
+}
 
-pub struct InvadersMovingSound {
-    index: usize,
-    sounds: [Handle<AudioSource>; 4],
+/// Source of all gameplay randomness, reseeded at the start of every game so that
+/// the same seed and the same inputs always produce the same game.
+#[derive(Resource)]
+pub struct GameRng {
+    rng: ChaCha8Rng,
+    seed: u64,
+    fixed_seed: Option<u64>,
+    games: u64,
 }
 
-impl InvadersMovingSound {
-    pub fn new(index: usize, sounds: [Handle<AudioSource>; 4]) -> Self {
-        Self { index, sounds }
+impl GameRng {
+    /// With a fixed seed, the n-th game of the session uses `seed + n`; otherwise
+    /// every game draws a new seed.
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(random);
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            seed,
+            fixed_seed,
+            games: 0,
+        }
     }
 
-    pub fn get(&mut self) -> Handle<AudioSource> {
-        let source = self.sounds[self.index].clone();
-        self.index = (self.index + 1) % self.sounds.len();
-        source
+    /// Reseed the generator for a new game.
+    pub fn next_game(&mut self) {
+        self.seed = match self.fixed_seed {
+            Some(seed) => seed.wrapping_add(self.games),
+            None => random(),
+        };
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+        self.games += 1;
+    }
+
+    /// Seed of the current game.
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 }
 
-#[derive(Resource)]
-pub struct ButtonHoveredSound(pub Handle<AudioSource>);
+impl Deref for GameRng {
+    type Target = ChaCha8Rng;
 
-#[derive(Resource)]
-pub struct ButtonPressedSound(pub Handle<AudioSource>);
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for GameRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
 
 #[derive(Resource)]
 pub struct AlreadyPlayed(pub bool);
@@ -37,23 +84,16 @@ pub struct PlayerScore(pub u32);
 #[derive(Resource)]
 pub struct BestScore(pub u32);
 
+/// Number of lasers fired by the player since the start of the game.
 #[derive(Resource)]
-pub struct ShootSound(pub Handle<AudioSource>);
-
-#[derive(Resource)]
-pub struct ExplosionSound(pub Handle<AudioSource>);
-
-#[derive(Resource)]
-pub struct InvaderKilledSound(pub Handle<AudioSource>);
-
-#[derive(Deref, DerefMut, Resource)]
-pub struct AlienSounds(pub InvadersMovingSound);
+pub struct ShotsFired(pub u32);
 
 #[derive(Deref, DerefMut, Resource)]
 pub struct AlienTimer(pub Timer);
 
-#[derive(Deref, DerefMut, Resource)]
-pub struct AlienTimerDuration(pub Duration);
+/// Index of the wave being played, counting from zero since the start of the game.
+#[derive(Resource)]
+pub struct CurrentWave(pub usize);
 
 #[derive(Deref, DerefMut, Resource)]
 pub struct UfoTimer(pub Timer);
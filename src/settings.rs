@@ -1,3 +1,5 @@
+use crate::game::aliens::MarchMode;
+use crate::game::lasers::Projectile;
 use bevy::prelude::*;
 
 pub const WINDOW_VISIBLE_DELAY: u32 = 3;
@@ -5,9 +7,11 @@ pub const WINDOW_VISIBLE_DELAY: u32 = 3;
 pub const PLAYER_SIZE: Vec2 = Vec2::new(60.0, 30.0);
 pub const PLAYER_SPEED: f32 = 300.0;
 
+pub const GAMEPAD_DEAD_ZONE: f32 = 0.5;
+
 pub const NUM_SHELTERS: usize = 4;
 pub const SHELTER_SIZE: Vec2 = Vec2::new(100.0, 50.0);
-pub const INITIAL_ARMOR_VALUE: u32 = 100;
+pub const SHELTER_BLAST_RADIUS: f32 = 7.0;
 
 pub const ALIENS_PER_LINE: usize = 11;
 pub const NUM_YELLOW: usize = 1_usize;
@@ -16,13 +20,22 @@ pub const NUM_RED: usize = 2;
 pub const SPACE_BETWEEN_ALIENS: Vec2 = Vec2::new(20.0, 16.0);
 pub const MARGIN: f32 = 80.0;
 pub const ALIEN_SHOOT_PROB: f32 = 1.0 / 40.0 / 30.0;
+pub const TARGETED_SHOT_PROB: f32 = 0.5;
 pub const ALIEN_SIZE: Vec2 = Vec2::new(40.0, 30.0);
 pub const YELLOW_ALIEN_VALUE: u32 = 30;
 pub const GREEN_ALIEN_VALUE: u32 = 20;
 pub const RED_ALIEN_VALUE: u32 = 10;
+pub const YELLOW_PROJECTILE: Projectile = Projectile::Squiggly;
+pub const GREEN_PROJECTILE: Projectile = Projectile::Plunger;
+pub const RED_PROJECTILE: Projectile = Projectile::Rolling;
 pub const ALIEN_TICK_DURATION: f32 = 0.8;
+pub const MARCH_MODE: MarchMode = MarchMode::Formation;
 
-pub const UFO_VALUE: u32 = 300;
+/// Value of the mystery ship after each number of shots, cycling like the original arcade
+/// where the 8th shot and every 15th after it, the 23rd, the 38th and so on, are worth 300.
+pub const UFO_SCORE_TABLE: [u32; 15] = [
+    100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100,
+];
 pub const UFO_SPAWN_PROB: f32 = 1.0 / 30.0;
 pub const UFO_SIZE: Vec2 = Vec2::new(82.0, 36.0);
 pub const UFO_SPEED: f32 = 150.0;
@@ -31,9 +44,9 @@ pub const LASER_SIZE: Vec2 = Vec2::new(5.0, 15.0);
 pub const PLAYER_LASER_SPEED: f32 = 600.0;
 pub const ALIEN_LASER_SPEED: f32 = 300.0;
 pub const MAX_ALIEN_LASERS: usize = 4;
-pub const LASER_DAMAGE: u32 = 5;
 
 pub const EXPLOSION_DURATION: f32 = 0.35;
+pub const ALIEN_EXPLOSION_DURATION: f32 = 0.25;
 pub const EXPLOSION_MIN_RADIUS: f32 = 13.0;
 pub const EXPLOSION_MAX_RADIUS: f32 = 30.0;
 
@@ -56,3 +69,5 @@ pub const NORMAL_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 pub const HOVERED_BUTTON: Color = Color::rgb(0.35, 0.35, 0.35);
 pub const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 pub const PRESSED_BUTTON: Color = Color::rgb(0.45, 0.85, 0.45);
+pub const FOCUS_RING_COLOR: Color = Color::WHITE;
+pub const FOCUS_RING_WIDTH: f32 = 3.0;
@@ -0,0 +1,68 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+const APP_DIR: &str = "invaders";
+const ASSETS_DIR: &str = "assets";
+
+/// Location of `path` in the assets folder, found the same way as the asset server
+/// does rather than from the working directory.
+pub fn asset_path(path: impl AsRef<Path>) -> PathBuf {
+    FileAssetReader::get_base_path().join(ASSETS_DIR).join(path)
+}
+
+/// Location of `file_name` in the data directory of the user.
+pub fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR).join(file_name))
+}
+
+/// Location of `file_name` in the configuration directory of the user.
+pub fn config_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR).join(file_name))
+}
+
+/// Load the `what` stored at `path`.
+///
+/// A missing file gives the default value. An unreadable one is also replaced by
+/// the default value, after being moved aside so that its content is not lost.
+pub fn load<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return T::default(),
+        Err(err) => {
+            warn!("Failed to read the {what} from {}: {err}", path.display());
+            return T::default();
+        }
+    };
+
+    match ron::from_str(&contents) {
+        Ok(value) => value,
+        Err(err) => {
+            let backup = path.with_extension("ron.corrupted");
+            warn!(
+                "Corrupted {what} in {} ({err}), moving them to {}",
+                path.display(),
+                backup.display()
+            );
+            if let Err(err) = fs::rename(path, &backup) {
+                warn!("Failed to move the corrupted {what}: {err}");
+            }
+            T::default()
+        }
+    }
+}
+
+/// Write `value` to `path`, without leaving a partially written file behind.
+pub fn save<T: Serialize>(value: &T, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents = ron::ser::to_string_pretty(value, Default::default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let temporary = path.with_extension("ron.tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)
+}
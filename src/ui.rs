@@ -1,17 +1,39 @@
+pub mod credits;
+pub mod how_to_play;
 pub mod menu;
+pub mod name_entry;
+pub mod navigation;
 pub mod panel;
 pub mod pause;
+pub mod quit_confirmation;
+pub mod scores;
+pub mod settings;
+pub mod widgets;
 
 use bevy::prelude::*;
+pub use credits::CreditsPlugin;
+pub use how_to_play::HowToPlayPlugin;
 pub use menu::MenuPlugin;
+pub use name_entry::NameEntryPlugin;
+pub use navigation::NavigationPlugin;
 pub use panel::PanelPlugin;
 pub use pause::PausePlugin;
+pub use quit_confirmation::QuitConfirmationPlugin;
+pub use scores::ScoresPlugin;
+pub use settings::SettingsPlugin;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(MenuPlugin)
+            .add_plugins(NameEntryPlugin)
+            .add_plugins(NavigationPlugin)
+            .add_plugins(SettingsPlugin)
+            .add_plugins(ScoresPlugin)
+            .add_plugins(HowToPlayPlugin)
+            .add_plugins(CreditsPlugin)
+            .add_plugins(QuitConfirmationPlugin)
             .add_plugins(PausePlugin)
             .add_plugins(PanelPlugin);
     }
@@ -0,0 +1,21 @@
+pub mod systems;
+
+use crate::despawn_screen;
+use crate::ui::menu::MenuScreen;
+use bevy::prelude::*;
+use systems::*;
+
+#[derive(Component)]
+pub struct OnCreditsScreen;
+
+pub struct CreditsPlugin;
+
+impl Plugin for CreditsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Credits), credits_setup)
+            .add_systems(
+                OnExit(MenuScreen::Credits),
+                despawn_screen::<OnCreditsScreen>,
+            );
+    }
+}
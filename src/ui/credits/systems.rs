@@ -0,0 +1,50 @@
+use crate::config::GameConfig;
+use crate::ui::credits::OnCreditsScreen;
+use crate::ui::widgets::{spawn_back_button, spawn_screen, spawn_title};
+use bevy::prelude::*;
+
+const CREDITS: [(&str, &str); 5] = [
+    ("Code", "MIT License"),
+    ("Icon", "hiclipart.com"),
+    ("Music", "Retro Arcade by Beat Mekanik"),
+    ("Sounds", "classicgaming.cc and gamesounds.xyz"),
+    ("Font", "Retro Gaming from dafont.com"),
+];
+
+pub fn credits_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let heading_style = TextStyle {
+        font_size: config.score_menu_text_size,
+        color: config.menu_text_color,
+        font: font.clone(),
+    };
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        ..heading_style.clone()
+    };
+
+    spawn_screen(&mut commands, OnCreditsScreen, |parent| {
+        spawn_title(parent, &config, font.clone(), "Credits");
+
+        for (heading, credit) in CREDITS {
+            parent.spawn(
+                TextBundle::from_section(heading.to_uppercase(), heading_style.clone()).with_style(
+                    Style {
+                        margin: UiRect::top(Val::Px(12.0)),
+                        ..default()
+                    },
+                ),
+            );
+            parent.spawn(TextBundle::from_section(
+                credit.to_uppercase(),
+                text_style.clone(),
+            ));
+        }
+
+        spawn_back_button(parent, &config, font);
+    });
+}
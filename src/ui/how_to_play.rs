@@ -0,0 +1,21 @@
+pub mod systems;
+
+use crate::despawn_screen;
+use crate::ui::menu::MenuScreen;
+use bevy::prelude::*;
+use systems::*;
+
+#[derive(Component)]
+pub struct OnHowToPlayScreen;
+
+pub struct HowToPlayPlugin;
+
+impl Plugin for HowToPlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::HowToPlay), how_to_play_setup)
+            .add_systems(
+                OnExit(MenuScreen::HowToPlay),
+                despawn_screen::<OnHowToPlayScreen>,
+            );
+    }
+}
@@ -0,0 +1,69 @@
+use crate::config::GameConfig;
+use crate::game::input::Action;
+use crate::ui::how_to_play::OnHowToPlayScreen;
+use crate::ui::widgets::{spawn_back_button, spawn_screen, spawn_title};
+use bevy::prelude::*;
+
+pub fn how_to_play_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let text_style = TextStyle {
+        font_size: config.score_menu_text_size,
+        color: config.menu_text_color,
+        font: font.clone(),
+    };
+
+    let keys = |action| {
+        config
+            .key_bindings
+            .keys(action)
+            .iter()
+            .map(|key| format!("{key:?}"))
+            .collect::<Vec<_>>()
+            .join(" or ")
+    };
+    let lines = [
+        format!(
+            "Move with {} and {}",
+            keys(Action::MoveLeft),
+            keys(Action::MoveRight)
+        ),
+        format!("Fire with {}", keys(Action::Fire)),
+        format!("Pause with {}", keys(Action::Pause)),
+        format!("Mute with {}", keys(Action::Mute)),
+        String::new(),
+        "Shoot every alien before they land".to_string(),
+        "Shelters protect you until they crumble".to_string(),
+        "Clearing a wave gives an extra life".to_string(),
+        String::new(),
+        format!(
+            "Yellow {}  Green {}  Red {}  Mystery {}-{}",
+            config.yellow_alien_value,
+            config.green_alien_value,
+            config.red_alien_value,
+            config.ufo_score_table.iter().min().unwrap_or(&0),
+            config.ufo_score_table.iter().max().unwrap_or(&0)
+        ),
+    ];
+
+    spawn_screen(&mut commands, OnHowToPlayScreen, |parent| {
+        spawn_title(parent, &config, font.clone(), "How to play");
+
+        for line in lines {
+            parent.spawn(
+                TextBundle::from_section(line.to_uppercase(), text_style.clone()).with_style(
+                    Style {
+                        min_height: Val::Px(config.score_menu_text_size),
+                        margin: UiRect::vertical(Val::Px(4.0)),
+                        ..default()
+                    },
+                ),
+            );
+        }
+
+        spawn_back_button(parent, &config, font);
+    });
+}
@@ -4,13 +4,54 @@ use crate::{despawn_screen, AppState};
 use bevy::prelude::*;
 use systems::*;
 
+/// The button focused for keyboard and gamepad navigation.
 #[derive(Component)]
 pub struct SelectedOption;
 
+/// Screen shown on the menu or over a paused game, the top of the [`MenuStack`].
+///
+/// Each screen spawns its nodes when entered and despawns them with its own
+/// marker when left.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum MenuScreen {
+    Main,
+    Settings,
+    HighScores,
+    HowToPlay,
+    Credits,
+    QuitConfirmation,
+    Pause,
+    #[default]
+    Disabled,
+}
+
+/// The screens opened since the main menu or the pause menu, the one shown last.
+#[derive(Resource, Default, Debug)]
+pub struct MenuStack(pub Vec<MenuScreen>);
+
+/// Request to open a screen over the current one or to go back to the previous one.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum MenuNavigation {
+    Push(MenuScreen),
+    Pop,
+}
+
+/// Systems of the screens run before the navigation between them, so that a screen
+/// can take over an input that would otherwise go back.
+#[derive(SystemSet, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MenuSet {
+    Screens,
+    Navigation,
+}
+
+/// Button going back to the previous screen.
+#[derive(Component)]
+pub struct BackButton;
+
 #[derive(Component)]
 pub enum MenuButtonAction {
     Play,
-    Quit,
+    Open(MenuScreen),
 }
 
 #[derive(Component)]
@@ -20,11 +61,27 @@ pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Menu), menu_setup)
+        app.add_state::<MenuScreen>()
+            .init_resource::<MenuStack>()
+            .add_event::<MenuNavigation>()
+            .configure_sets(Update, (MenuSet::Screens, MenuSet::Navigation).chain())
+            .add_systems(OnEnter(AppState::Menu), open_main_menu)
+            .add_systems(OnExit(AppState::Menu), close_menu)
+            .add_systems(
+                Update,
+                (go_back, navigate_menu)
+                    .chain()
+                    .in_set(MenuSet::Navigation)
+                    .run_if(not(in_state(MenuScreen::Disabled))),
+            )
+            .add_systems(OnEnter(MenuScreen::Main), menu_setup)
             .add_systems(
                 Update,
-                (menu_action, button_system).run_if(in_state(AppState::Menu)),
+                menu_action
+                    .in_set(MenuSet::Screens)
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(in_state(MenuScreen::Main)),
             )
-            .add_systems(OnExit(AppState::Menu), despawn_screen::<OnMenuScreen>);
+            .add_systems(OnExit(MenuScreen::Main), despawn_screen::<OnMenuScreen>);
     }
 }
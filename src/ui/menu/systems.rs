@@ -1,176 +1,169 @@
+use crate::config::GameConfig;
+use crate::game::input::MenuInput;
 use crate::game::GameState;
 use crate::resources::*;
-use crate::resources::{AlreadyPlayed, ButtonHoveredSound, ButtonPressedSound};
-use crate::settings::*;
 use crate::ui::menu::*;
+use crate::ui::navigation::activated;
+use crate::ui::widgets::{button_style, spawn_button, spawn_screen, spawn_title};
 use crate::AppState;
-use bevy::app::AppExit;
 use bevy::prelude::*;
 
+pub fn open_main_menu(
+    mut menu_stack: ResMut<MenuStack>,
+    mut menu_screen: ResMut<NextState<MenuScreen>>,
+) {
+    menu_stack.0 = vec![MenuScreen::Main];
+    menu_screen.set(MenuScreen::Main);
+}
+
+pub fn close_menu(
+    mut menu_stack: ResMut<MenuStack>,
+    mut menu_screen: ResMut<NextState<MenuScreen>>,
+) {
+    menu_stack.0.clear();
+    menu_screen.set(MenuScreen::Disabled);
+}
+
+pub fn go_back(
+    interaction_query: Query<(Ref<Interaction>, Has<SelectedOption>), With<BackButton>>,
+    menu_input: Res<MenuInput>,
+    menu_screen: Res<State<MenuScreen>>,
+    mut navigation_events: EventWriter<MenuNavigation>,
+) {
+    let back_button = interaction_query
+        .iter()
+        .any(|(interaction, selected)| activated(&interaction, selected, &menu_input));
+    if back_button || menu_input.back {
+        // Going back from the main menu leaves the game, once confirmed.
+        if *menu_screen.get() == MenuScreen::Main {
+            navigation_events.send(MenuNavigation::Push(MenuScreen::QuitConfirmation));
+        } else {
+            navigation_events.send(MenuNavigation::Pop);
+        }
+    }
+}
+
+pub fn navigate_menu(
+    mut navigation_events: EventReader<MenuNavigation>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut menu_screen: ResMut<NextState<MenuScreen>>,
+) {
+    if navigation_events.is_empty() {
+        return;
+    }
+    for event in navigation_events.read() {
+        match event {
+            MenuNavigation::Push(screen) => menu_stack.0.push(*screen),
+            // The main menu or the pause menu stays at the bottom of the stack.
+            MenuNavigation::Pop => {
+                if menu_stack.0.len() > 1 {
+                    menu_stack.0.pop();
+                }
+            }
+        }
+    }
+    // A screen may have closed the menus in the meantime.
+    if let Some(screen) = menu_stack.0.last() {
+        menu_screen.set(*screen);
+    }
+}
+
 pub fn menu_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
     already_played: Res<AlreadyPlayed>,
     player_score: Res<PlayerScore>,
     best_score: Res<BestScore>,
+    rng: Res<GameRng>,
 ) {
-    let button_style = Style {
-        width: Val::Px(BUTTON_WIDTH),
-        height: Val::Px(BUTTON_HEIGHT),
-        margin: UiRect::all(Val::Px(BUTTON_MARGIN)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
+    let font = asset_server.load("fonts/font.ttf");
     let button_text_style = TextStyle {
-        font_size: TEXT_BUTTON_SIZE,
+        font_size: config.text_button_size,
         color: Color::WHITE,
-        font: asset_server.load("fonts/font.ttf"),
+        font: font.clone(),
     };
+    let play_button_text = if already_played.0 { "Replay" } else { "Play" };
+    let buttons = [
+        (play_button_text, MenuButtonAction::Play),
+        ("Settings", MenuButtonAction::Open(MenuScreen::Settings)),
+        ("Scores", MenuButtonAction::Open(MenuScreen::HighScores)),
+        ("Help", MenuButtonAction::Open(MenuScreen::HowToPlay)),
+        ("Credits", MenuButtonAction::Open(MenuScreen::Credits)),
+        ("Quit", MenuButtonAction::Open(MenuScreen::QuitConfirmation)),
+    ];
+    // Two buttons per line.
+    let grid_width = 2.0 * (config.button_width + 2.0 * config.button_margin);
 
-    commands
-        .spawn((
-            NodeBundle {
+    spawn_screen(&mut commands, OnMenuScreen, |parent| {
+        spawn_title(parent, &config, font.clone(), "Invaders");
+
+        parent
+            .spawn(NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
+                    width: Val::Px(grid_width),
+                    flex_wrap: FlexWrap::Wrap,
                     ..default()
                 },
                 ..default()
-            },
-            OnMenuScreen,
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    background_color: Color::BLACK.into(),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(
-                        TextBundle::from_section(
-                            "Invaders".to_uppercase(),
-                            TextStyle {
-                                font_size: MENU_TITLE_SIZE,
-                                color: MENU_TEXT_COLOR,
-                                font: asset_server.load("fonts/font.ttf"),
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::all(Val::Px(50.0)),
-                            ..default()
-                        }),
+            })
+            .with_children(|parent| {
+                for (text, action) in buttons {
+                    spawn_button(
+                        parent,
+                        &config,
+                        button_style(&config),
+                        action,
+                        TextBundle::from_section(text.to_uppercase(), button_text_style.clone()),
                     );
-
-                    let play_button_text = if already_played.0 { "Replay" } else { "Play" };
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: NORMAL_BUTTON.into(),
-                                ..default()
-                            },
-                            MenuButtonAction::Play,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                play_button_text.to_uppercase(),
-                                button_text_style.clone(),
-                            ));
-                        });
-
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: NORMAL_BUTTON.into(),
-                                ..default()
-                            },
-                            MenuButtonAction::Quit,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Quit".to_uppercase(),
-                                button_text_style,
-                            ));
-                        });
-
-                    if already_played.0 {
-                        parent.spawn(
-                            TextBundle::from_section(
-                                format!("Score: {} / Best score: {}", player_score.0, best_score.0)
-                                    .to_uppercase(),
-                                TextStyle {
-                                    font_size: SCORE_MENU_TEXT_SIZE,
-                                    color: MENU_TEXT_COLOR,
-                                    font: asset_server.load("fonts/font.ttf"),
-                                },
-                            )
-                            .with_style(Style {
-                                margin: UiRect::all(Val::Px(30.0)),
-                                ..default()
-                            }),
-                        );
-                    }
-                });
-        });
-}
-
-pub fn button_system(
-    mut commands: Commands,
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
-        (Changed<Interaction>, With<Button>),
-    >,
-    hovered_sound: Res<ButtonHoveredSound>,
-    pressed_sound: Res<ButtonPressedSound>,
-) {
-    for (interaction, mut color, selected) in &mut interaction_query {
-        if *interaction == Interaction::Hovered {
-            commands.spawn(AudioBundle {
-                source: hovered_sound.0.clone(),
-                settings: PlaybackSettings::DESPAWN,
-            });
-        }
-        if *interaction == Interaction::Pressed {
-            commands.spawn(AudioBundle {
-                source: pressed_sound.0.clone(),
-                settings: PlaybackSettings::DESPAWN,
+                }
             });
+
+        if already_played.0 {
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "Score: {} / Best score: {}\nSeed: {}",
+                        player_score.0,
+                        best_score.0,
+                        rng.seed()
+                    )
+                    .to_uppercase(),
+                    TextStyle {
+                        font_size: config.score_menu_text_size,
+                        color: config.menu_text_color,
+                        font,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(30.0)),
+                    ..default()
+                }),
+            );
         }
-        *color = match (*interaction, selected) {
-            (Interaction::Pressed, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON.into(),
-            (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON.into(),
-            (Interaction::Hovered, None) => HOVERED_BUTTON.into(),
-            (Interaction::None, None) => NORMAL_BUTTON.into(),
-        }
-    }
+    });
 }
 
 pub fn menu_action(
     interaction_query: Query<
-        (&Interaction, &MenuButtonAction),
-        (Changed<Interaction>, With<Button>),
+        (Ref<Interaction>, &MenuButtonAction, Has<SelectedOption>),
+        With<Button>,
     >,
-    mut app_exit_events: EventWriter<AppExit>,
+    menu_input: Res<MenuInput>,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut navigation_events: EventWriter<MenuNavigation>,
 ) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
+    for (interaction, menu_button_action, selected) in &interaction_query {
+        if activated(&interaction, selected, &menu_input) {
             match menu_button_action {
                 MenuButtonAction::Play => {
                     app_state.set(AppState::InGame);
                     game_state.set(GameState::Running);
                 }
-                MenuButtonAction::Quit => app_exit_events.send(AppExit),
+                MenuButtonAction::Open(screen) => {
+                    navigation_events.send(MenuNavigation::Push(*screen))
+                }
             }
         }
     }
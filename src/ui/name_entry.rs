@@ -0,0 +1,65 @@
+pub mod systems;
+
+use crate::high_scores::NAME_LENGTH;
+use crate::{despawn_screen, AppState};
+use bevy::prelude::*;
+use systems::*;
+
+/// The name being entered for a new high score, one letter at a time.
+#[derive(Resource)]
+pub struct NameEntry {
+    pub letters: [char; NAME_LENGTH],
+    pub cursor: usize,
+}
+
+impl Default for NameEntry {
+    fn default() -> Self {
+        Self {
+            letters: ['A'; NAME_LENGTH],
+            cursor: 0,
+        }
+    }
+}
+
+impl NameEntry {
+    pub fn name(&self) -> String {
+        self.letters.iter().collect()
+    }
+
+    /// Move the letter under the cursor `offset` places through the alphabet.
+    pub fn cycle(&mut self, offset: i8) {
+        let letter = self.letters[self.cursor] as u8 - b'A';
+        let letter = (letter as i8 + offset).rem_euclid(26) as u8;
+        self.letters[self.cursor] = (b'A' + letter) as char;
+    }
+
+    /// Put `letter` under the cursor then move to the next one.
+    pub fn type_letter(&mut self, letter: char) {
+        self.letters[self.cursor] = letter.to_ascii_uppercase();
+        self.cursor = (self.cursor + 1).min(NAME_LENGTH - 1);
+    }
+}
+
+#[derive(Component)]
+pub struct OnNameEntryScreen;
+
+#[derive(Component)]
+pub struct NameEntryText;
+
+pub struct NameEntryPlugin;
+
+impl Plugin for NameEntryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::NameEntry), name_entry_setup)
+            .add_systems(
+                Update,
+                (handle_name_entry_input, update_name_entry_text)
+                    .chain()
+                    .run_if(in_state(AppState::NameEntry)),
+            )
+            .add_systems(
+                OnExit(AppState::NameEntry),
+                (despawn_screen::<OnNameEntryScreen>, remove_name_entry),
+            );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::config::GameConfig;
+use crate::game::input::MenuInput;
+use crate::high_scores::{today, HighScore, HighScores, HighScoresPath};
+use crate::resources::{CurrentWave, GameRng, PlayerScore};
+use crate::ui::name_entry::*;
+use crate::AppState;
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+pub fn name_entry_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    score: Res<PlayerScore>,
+) {
+    commands.insert_resource(NameEntry::default());
+
+    let font = asset_server.load("fonts/font.ttf");
+    let text_style = TextStyle {
+        font_size: config.score_menu_text_size,
+        color: config.menu_text_color,
+        font: font.clone(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnNameEntryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "New high score".to_uppercase(),
+                    TextStyle {
+                        font_size: config.menu_title_size,
+                        color: config.menu_text_color,
+                        font: font.clone(),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(30.0)),
+                    ..default()
+                }),
+            );
+
+            parent.spawn(TextBundle::from_section(
+                format!("Score: {}", score.0).to_uppercase(),
+                text_style.clone(),
+            ));
+
+            parent.spawn((
+                TextBundle::from_sections((0..NAME_LENGTH).map(|_| {
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: config.menu_title_size * 1.5,
+                            ..text_style.clone()
+                        },
+                    )
+                }))
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(30.0)),
+                    ..default()
+                }),
+                NameEntryText,
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                "Up/Down or type to change a letter\nSpace or Enter to confirm".to_uppercase(),
+                TextStyle {
+                    font_size: config.score_menu_text_size * 0.6,
+                    ..text_style
+                },
+            ));
+        });
+}
+
+pub fn handle_name_entry_input(
+    menu_input: Res<MenuInput>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    path: Res<HighScoresPath>,
+    score: Res<PlayerScore>,
+    current_wave: Res<CurrentWave>,
+    rng: Res<GameRng>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    // Letters typed while the game was still running must not fill in the name. The
+    // entry is removed when the screen is left, so that it is added anew every time.
+    if name_entry.is_added() {
+        received_characters.clear();
+        return;
+    }
+
+    for event in received_characters.read() {
+        if event.char.is_ascii_alphabetic() {
+            name_entry.type_letter(event.char);
+        }
+    }
+
+    if menu_input.up {
+        name_entry.cycle(1);
+    }
+    if menu_input.down {
+        name_entry.cycle(-1);
+    }
+    if menu_input.left || menu_input.back {
+        name_entry.cursor = name_entry.cursor.saturating_sub(1);
+    }
+    if menu_input.right {
+        name_entry.cursor = (name_entry.cursor + 1).min(NAME_LENGTH - 1);
+    }
+
+    if menu_input.confirm {
+        high_scores.record(
+            HighScore {
+                name: name_entry.name(),
+                score: score.0,
+                wave: current_wave.0 + 1,
+                date: today(),
+                seed: rng.seed(),
+            },
+            &path,
+        );
+        next_app_state.set(AppState::Menu);
+    }
+}
+
+pub fn update_name_entry_text(
+    name_entry: Res<NameEntry>,
+    config: Res<GameConfig>,
+    mut text_query: Query<&mut Text, With<NameEntryText>>,
+) {
+    if !name_entry.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        for (i, section) in text.sections.iter_mut().enumerate() {
+            section.value = format!(" {} ", name_entry.letters[i]);
+            // The letter being edited stands out from the others.
+            section.style.color = if i == name_entry.cursor {
+                config.pressed_button
+            } else {
+                config.menu_text_color
+            };
+        }
+    }
+}
+
+pub fn remove_name_entry(mut commands: Commands) {
+    commands.remove_resource::<NameEntry>();
+}
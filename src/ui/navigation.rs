@@ -0,0 +1,27 @@
+pub mod systems;
+
+use crate::game::input::MenuInput;
+use bevy::prelude::*;
+use systems::*;
+
+/// Move the focus between the buttons of whichever screen is shown with the
+/// keyboard or a gamepad, and colour them.
+///
+/// The focused button holds the `SelectedOption` component. Screens react to it
+/// being activated with [`activated`].
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (focus_hovered_button, navigate_buttons, button_system).chain(),
+        );
+    }
+}
+
+/// Whether a button was clicked, or activated from the keyboard or a gamepad while focused.
+pub fn activated(interaction: &Ref<Interaction>, selected: bool, menu_input: &MenuInput) -> bool {
+    let clicked = interaction.is_changed() && **interaction == Interaction::Pressed;
+    clicked || (selected && menu_input.confirm)
+}
@@ -0,0 +1,131 @@
+use crate::config::GameConfig;
+use crate::game::input::MenuInput;
+use crate::game::GameEvent;
+use crate::ui::menu::SelectedOption;
+use bevy::prelude::*;
+
+/// Penalty given to the distance across the direction of navigation, so that
+/// buttons in line with the focused one are preferred.
+const ALIGNMENT_WEIGHT: f32 = 2.0;
+
+pub fn focus_hovered_button(
+    mut commands: Commands,
+    interaction_query: Query<(Entity, Ref<Interaction>), With<Button>>,
+    selected_query: Query<Entity, (With<SelectedOption>, With<Button>)>,
+) {
+    let hovered = interaction_query
+        .iter()
+        .find(|(_, interaction)| interaction.is_changed() && **interaction == Interaction::Hovered);
+    if let Some((entity, _)) = hovered {
+        for selected in &selected_query {
+            commands.entity(selected).remove::<SelectedOption>();
+        }
+        commands.entity(entity).insert(SelectedOption);
+    }
+}
+
+pub fn navigate_buttons(
+    mut commands: Commands,
+    button_query: Query<(Entity, &Node, &GlobalTransform, Has<SelectedOption>), With<Button>>,
+    menu_input: Res<MenuInput>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    // Buttons are only placed once the layout of their screen has been computed.
+    let buttons: Vec<_> = button_query
+        .iter()
+        .filter(|(_, node, _, _)| node.size() != Vec2::ZERO)
+        .map(|(entity, _, transform, selected)| {
+            (entity, transform.translation().truncate(), selected)
+        })
+        .collect();
+
+    let Some(&(selected, position, _)) = buttons.iter().find(|(_, _, selected)| *selected) else {
+        // Focus the first button of a new screen, in reading order.
+        let first = buttons
+            .iter()
+            .min_by(|(_, a, _), (_, b, _)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap());
+        if let Some((entity, _, _)) = first {
+            commands.entity(*entity).insert(SelectedOption);
+        }
+        return;
+    };
+
+    // The y axis of the interface points down.
+    let direction = match *menu_input {
+        MenuInput { up: true, .. } => Vec2::NEG_Y,
+        MenuInput { down: true, .. } => Vec2::Y,
+        MenuInput { left: true, .. } => Vec2::NEG_X,
+        MenuInput { right: true, .. } => Vec2::X,
+        _ => return,
+    };
+
+    let candidates = buttons
+        .iter()
+        .filter(|(entity, _, _)| *entity != selected)
+        .map(|(entity, other, _)| {
+            let offset = *other - position;
+            let along = offset.dot(direction);
+            let across = offset.perp_dot(direction).abs();
+            (*entity, along, along + ALIGNMENT_WEIGHT * across)
+        });
+    // Past the last button in that direction, wrap around to the farthest one in
+    // the opposite direction.
+    let next = candidates
+        .clone()
+        .filter(|(_, along, _)| *along > f32::EPSILON)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .or_else(|| {
+            candidates
+                .filter(|(_, along, _)| *along < -f32::EPSILON)
+                .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        });
+
+    if let Some((entity, _, _)) = next {
+        commands.entity(selected).remove::<SelectedOption>();
+        commands.entity(entity).insert(SelectedOption);
+        game_events.send(GameEvent::ButtonHovered);
+    }
+}
+
+pub fn button_system(
+    mut interaction_query: Query<
+        (
+            Ref<Interaction>,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            Has<SelectedOption>,
+        ),
+        With<Button>,
+    >,
+    config: Res<GameConfig>,
+    menu_input: Res<MenuInput>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    for (interaction, mut color, mut border_color, selected) in &mut interaction_query {
+        if interaction.is_changed() && *interaction == Interaction::Hovered {
+            game_events.send(GameEvent::ButtonHovered);
+        }
+        let pressed = interaction.is_changed() && *interaction == Interaction::Pressed;
+        if pressed || (selected && menu_input.confirm) {
+            game_events.send(GameEvent::ButtonPressed);
+        }
+
+        let next_color = match (*interaction, selected) {
+            (Interaction::Pressed, _) | (Interaction::None, true) => config.pressed_button,
+            (Interaction::Hovered, true) => config.hovered_pressed_button,
+            (Interaction::Hovered, false) => config.hovered_button,
+            (Interaction::None, false) => config.normal_button,
+        };
+        if color.0 != next_color {
+            color.0 = next_color;
+        }
+        let ring_color = if selected {
+            config.focus_ring_color
+        } else {
+            Color::NONE
+        };
+        if border_color.0 != ring_color {
+            border_color.0 = ring_color;
+        }
+    }
+}
@@ -1,39 +1,54 @@
+use crate::config::GameConfig;
 use crate::game::aliens::{Alien, Ufo};
 use crate::game::lasers::Laser;
 use crate::game::OnGameScreen;
 use crate::resources::{LivesRemaining, PlayerScore};
-use crate::settings::{SCOREBOARD_FONT_SIZE, TEXT_COLOR};
 use crate::ui::panel::*;
 use bevy::asset::AssetServer;
 use bevy::prelude::*;
 
-pub fn spawn_scoreboard(commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_scoreboard(
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
     spawn_text(
         commands,
         JustifyContent::Start,
         "Score=",
         UiPlayerScore,
         asset_server,
+        config,
     );
 }
 
-pub fn spawn_remaining_lives(commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_remaining_lives(
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
     spawn_text(
         commands,
         JustifyContent::End,
         "Lives=",
         UiLivesRemaining,
         asset_server,
+        config,
     );
 }
 
-pub fn spawn_remaining_aliens(commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_remaining_aliens(
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
     spawn_text(
         commands,
         JustifyContent::Center,
         "Aliens=",
         UiAliensRemaining,
         asset_server,
+        config,
     );
 }
 
@@ -43,6 +58,7 @@ fn spawn_text(
     text: impl ToString,
     component: impl Component,
     asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
 ) {
     let style = Style {
         width: Val::Percent(100.0),
@@ -71,14 +87,14 @@ fn spawn_text(
                             TextSection::new(
                                 text.to_string().to_uppercase(),
                                 TextStyle {
-                                    font_size: SCOREBOARD_FONT_SIZE,
-                                    color: TEXT_COLOR,
+                                    font_size: config.scoreboard_font_size,
+                                    color: config.text_color,
                                     font: font.clone(),
                                 },
                             ),
                             TextSection::from_style(TextStyle {
-                                font_size: SCOREBOARD_FONT_SIZE,
-                                color: TEXT_COLOR,
+                                font_size: config.scoreboard_font_size,
+                                color: config.text_color,
                                 font,
                             }),
                         ]),
@@ -1,10 +1,30 @@
 pub mod systems;
 
 use crate::despawn_screen;
+use crate::game::input::InputSource;
 use crate::game::GameState;
+use crate::ui::menu::{MenuScreen, MenuSet};
 use bevy::prelude::*;
 use systems::*;
 
+/// Seconds counted down before a resumed game runs again.
+const RESUME_COUNTDOWN: f32 = 3.0;
+
+#[derive(Component)]
+pub enum PauseButtonAction {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+/// Time left before the game resumes.
+#[derive(Resource)]
+pub struct ResumeCountdown(pub Timer);
+
+#[derive(Component)]
+pub struct CountdownText;
+
 #[derive(Component)]
 pub struct OnPauseScreen;
 
@@ -12,7 +32,33 @@ pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Pause), pause_setup)
-            .add_systems(OnExit(GameState::Pause), despawn_screen::<OnPauseScreen>);
+        // The player cannot take over a replay, which only shows that it is paused.
+        app.add_systems(
+            OnEnter(GameState::Pause),
+            (
+                open_pause_menu.run_if(resource_equals(InputSource::Live)),
+                pause_setup.run_if(resource_equals(InputSource::Replay)),
+            ),
+        )
+        .add_systems(OnEnter(MenuScreen::Pause), pause_menu_setup)
+        .add_systems(
+            Update,
+            pause_action
+                .in_set(MenuSet::Screens)
+                .run_if(in_state(MenuScreen::Pause)),
+        )
+        .add_systems(
+            Update,
+            count_down.run_if(resource_exists::<ResumeCountdown>()),
+        )
+        .add_systems(OnExit(MenuScreen::Pause), despawn_screen::<OnPauseScreen>)
+        .add_systems(
+            OnExit(GameState::Pause),
+            (
+                despawn_screen::<OnPauseScreen>,
+                despawn_screen::<CountdownText>,
+                remove_countdown,
+            ),
+        );
     }
 }
@@ -1,5 +1,14 @@
-use crate::settings::{SCOREBOARD_FONT_SIZE, TEXT_COLOR};
+use crate::config::GameConfig;
+use crate::game::input::{BufferedInput, MenuInput};
+use crate::game::systems::{finish_game, restart_game};
+use crate::game::GameState;
+use crate::high_scores::HighScores;
+use crate::resources::{AlienTimer, AlreadyPlayed, BestScore, PlayerScore};
+use crate::ui::menu::{MenuNavigation, MenuScreen, MenuStack, SelectedOption};
+use crate::ui::navigation::activated;
 use crate::ui::pause::*;
+use crate::ui::widgets::{button_style, spawn_button};
+use crate::AppState;
 use bevy::asset::AssetServer;
 use bevy::prelude::*;
 use bevy::prelude::{
@@ -7,13 +16,14 @@ use bevy::prelude::{
     TextStyle, Val,
 };
 
-pub fn pause_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_overlay(commands: &mut Commands, children: impl FnOnce(&mut ChildBuilder)) {
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
                     height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
                     ..default()
@@ -23,14 +33,183 @@ pub fn pause_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             OnPauseScreen,
         ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_sections([TextSection::new(
+        .with_children(children);
+}
+
+pub fn pause_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    spawn_overlay(&mut commands, |parent| {
+        parent.spawn(TextBundle::from_sections([TextSection::new(
+            "Pause".to_uppercase(),
+            TextStyle {
+                font_size: config.scoreboard_font_size,
+                color: config.text_color,
+                font: asset_server.load("fonts/font.ttf"),
+            },
+        )]));
+    });
+}
+
+pub fn open_pause_menu(
+    mut menu_stack: ResMut<MenuStack>,
+    mut menu_screen: ResMut<NextState<MenuScreen>>,
+) {
+    menu_stack.0 = vec![MenuScreen::Pause];
+    menu_screen.set(MenuScreen::Pause);
+}
+
+pub fn pause_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let button_text_style = TextStyle {
+        font_size: config.text_button_size,
+        color: Color::WHITE,
+        font: font.clone(),
+    };
+    let buttons = [
+        ("Resume", PauseButtonAction::Resume),
+        ("Restart", PauseButtonAction::Restart),
+        ("Settings", PauseButtonAction::Settings),
+        ("Menu", PauseButtonAction::QuitToMenu),
+    ];
+
+    spawn_overlay(&mut commands, |parent| {
+        parent.spawn(
+            TextBundle::from_section(
                 "Pause".to_uppercase(),
                 TextStyle {
-                    font_size: SCOREBOARD_FONT_SIZE,
-                    color: TEXT_COLOR,
-                    font: asset_server.load("fonts/font.ttf"),
+                    font_size: config.scoreboard_font_size,
+                    color: config.text_color,
+                    font,
                 },
-            )]));
-        });
+            )
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(30.0)),
+                ..default()
+            }),
+        );
+        for (text, action) in buttons {
+            spawn_button(
+                parent,
+                &config,
+                button_style(&config),
+                action,
+                TextBundle::from_section(text.to_uppercase(), button_text_style.clone()),
+            );
+        }
+    });
+}
+
+pub fn pause_action(
+    mut commands: Commands,
+    interaction_query: Query<
+        (Ref<Interaction>, &PauseButtonAction, Has<SelectedOption>),
+        With<Button>,
+    >,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    menu_input: Res<MenuInput>,
+    score: Res<PlayerScore>,
+    high_scores: Option<Res<HighScores>>,
+    mut best_score: ResMut<BestScore>,
+    mut already_played: ResMut<AlreadyPlayed>,
+    mut alien_timer: ResMut<AlienTimer>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut menu_screen: ResMut<NextState<MenuScreen>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut navigation_events: EventWriter<MenuNavigation>,
+) {
+    let mut action = interaction_query
+        .iter()
+        .find(|(interaction, _, selected)| activated(interaction, *selected, &menu_input))
+        .map(|(_, action, _)| action);
+    // The pause action and going back resume the game as well.
+    if menu_input.pause || menu_input.back {
+        action = Some(&PauseButtonAction::Resume);
+    }
+    let Some(action) = action else {
+        return;
+    };
+
+    if let PauseButtonAction::Settings = action {
+        navigation_events.send(MenuNavigation::Push(MenuScreen::Settings));
+        return;
+    }
+    menu_stack.0.clear();
+    menu_screen.set(MenuScreen::Disabled);
+
+    match action {
+        PauseButtonAction::Resume => {
+            // The game keeps paused until the countdown ends.
+            commands.insert_resource(ResumeCountdown(Timer::from_seconds(
+                RESUME_COUNTDOWN,
+                TimerMode::Once,
+            )));
+            commands.spawn((
+                TextBundle::from_section(
+                    format!("{RESUME_COUNTDOWN}"),
+                    TextStyle {
+                        font_size: config.menu_title_size,
+                        color: config.text_color,
+                        font: asset_server.load("fonts/font.ttf"),
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    align_self: AlignSelf::Center,
+                    justify_self: JustifySelf::Center,
+                    ..default()
+                }),
+                CountdownText,
+            ));
+        }
+        PauseButtonAction::Restart | PauseButtonAction::QuitToMenu => {
+            // Leave the pause the way the game would have resumed.
+            alien_timer.unpause();
+            game_state.set(GameState::Running);
+
+            if let PauseButtonAction::Restart = action {
+                commands.add(restart_game);
+            } else {
+                already_played.0 = true;
+                app_state.set(finish_game(
+                    &score,
+                    &mut best_score,
+                    high_scores.as_deref(),
+                    false,
+                ));
+            }
+        }
+        PauseButtonAction::Settings => {}
+    }
+}
+
+pub fn count_down(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut countdown: ResMut<ResumeCountdown>,
+    mut text_query: Query<&mut Text, With<CountdownText>>,
+    mut buffered_input: ResMut<BufferedInput>,
+) {
+    if countdown.0.tick(time.delta()).just_finished() {
+        // Unpause through the input of the player, so that replays unpause on the same tick.
+        buffered_input.0.pause = true;
+        commands.remove_resource::<ResumeCountdown>();
+        return;
+    }
+    let seconds_left = countdown.0.remaining_secs().ceil();
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("{seconds_left}");
+    }
+}
+
+pub fn remove_countdown(mut commands: Commands) {
+    commands.remove_resource::<ResumeCountdown>();
 }
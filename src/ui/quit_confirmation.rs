@@ -0,0 +1,34 @@
+pub mod systems;
+
+use crate::despawn_screen;
+use crate::ui::menu::{MenuScreen, MenuSet};
+use bevy::prelude::*;
+use systems::*;
+
+/// Button leaving the game for good, the back button stays on the menu.
+#[derive(Component)]
+pub struct ConfirmQuitButton;
+
+#[derive(Component)]
+pub struct OnQuitConfirmationScreen;
+
+pub struct QuitConfirmationPlugin;
+
+impl Plugin for QuitConfirmationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(MenuScreen::QuitConfirmation),
+            quit_confirmation_setup,
+        )
+        .add_systems(
+            Update,
+            confirm_quit
+                .in_set(MenuSet::Screens)
+                .run_if(in_state(MenuScreen::QuitConfirmation)),
+        )
+        .add_systems(
+            OnExit(MenuScreen::QuitConfirmation),
+            despawn_screen::<OnQuitConfirmationScreen>,
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use crate::config::GameConfig;
+use crate::game::input::MenuInput;
+use crate::ui::menu::{BackButton, SelectedOption};
+use crate::ui::navigation::activated;
+use crate::ui::quit_confirmation::{ConfirmQuitButton, OnQuitConfirmationScreen};
+use crate::ui::widgets::{button_style, spawn_button, spawn_screen, spawn_title};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+pub fn quit_confirmation_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let button_text_style = TextStyle {
+        font_size: config.text_button_size,
+        color: Color::WHITE,
+        font: font.clone(),
+    };
+
+    spawn_screen(&mut commands, OnQuitConfirmationScreen, |parent| {
+        spawn_title(parent, &config, font, "Quit the game?");
+
+        parent.spawn(NodeBundle::default()).with_children(|parent| {
+            // Staying is focused first so that a stray confirmation does not quit.
+            spawn_button(
+                parent,
+                &config,
+                button_style(&config),
+                BackButton,
+                TextBundle::from_section("No".to_uppercase(), button_text_style.clone()),
+            );
+            spawn_button(
+                parent,
+                &config,
+                button_style(&config),
+                ConfirmQuitButton,
+                TextBundle::from_section("Yes".to_uppercase(), button_text_style),
+            );
+        });
+    });
+}
+
+pub fn confirm_quit(
+    interaction_query: Query<(Ref<Interaction>, Has<SelectedOption>), With<ConfirmQuitButton>>,
+    menu_input: Res<MenuInput>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let confirmed = interaction_query
+        .iter()
+        .any(|(interaction, selected)| activated(&interaction, selected, &menu_input));
+    if confirmed {
+        app_exit_events.send(AppExit);
+    }
+}
@@ -0,0 +1,21 @@
+pub mod systems;
+
+use crate::despawn_screen;
+use crate::ui::menu::MenuScreen;
+use bevy::prelude::*;
+use systems::*;
+
+#[derive(Component)]
+pub struct OnScoresScreen;
+
+pub struct ScoresPlugin;
+
+impl Plugin for ScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::HighScores), scores_setup)
+            .add_systems(
+                OnExit(MenuScreen::HighScores),
+                despawn_screen::<OnScoresScreen>,
+            );
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::GameConfig;
+use crate::high_scores::HighScores;
+use crate::ui::scores::OnScoresScreen;
+use crate::ui::widgets::{spawn_back_button, spawn_screen, spawn_title};
+use bevy::prelude::*;
+
+pub fn scores_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    high_scores: Res<HighScores>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let text_style = TextStyle {
+        font_size: config.score_menu_text_size,
+        color: config.menu_text_color,
+        font: font.clone(),
+    };
+
+    spawn_screen(&mut commands, OnScoresScreen, |parent| {
+        spawn_title(parent, &config, font.clone(), "High scores");
+
+        let lines = if high_scores.0.is_empty() {
+            vec!["No high score yet".to_string()]
+        } else {
+            high_scores
+                .0
+                .iter()
+                .enumerate()
+                .map(|(rank, entry)| {
+                    format!(
+                        "{:>2}. {:<3} {:>6}  wave {:<2} {}",
+                        rank + 1,
+                        entry.name,
+                        entry.score,
+                        entry.wave,
+                        entry.date
+                    )
+                })
+                .collect()
+        };
+        for line in lines {
+            parent.spawn(
+                TextBundle::from_section(line.to_uppercase(), text_style.clone()).with_style(
+                    Style {
+                        margin: UiRect::vertical(Val::Px(4.0)),
+                        ..default()
+                    },
+                ),
+            );
+        }
+
+        spawn_back_button(parent, &config, font);
+    });
+}
@@ -0,0 +1,51 @@
+pub mod systems;
+
+use crate::despawn_screen;
+use crate::game::input::Action;
+use crate::ui::menu::{MenuScreen, MenuSet};
+use crate::user_settings::Setting;
+use bevy::prelude::*;
+use systems::*;
+
+#[derive(Component)]
+pub enum SettingsButtonAction {
+    Previous(Setting),
+    Next(Setting),
+    Rebind(Action),
+    ClearBinding(Action),
+}
+
+/// Text showing the current value of a setting.
+#[derive(Component)]
+pub struct SettingValue(pub Setting);
+
+/// Text showing the keys bound to an action.
+#[derive(Component)]
+pub struct BindingValue(pub Action);
+
+/// Action waiting for the player to press the key to bind to it.
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+#[derive(Component)]
+pub struct OnSettingsScreen;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(MenuScreen::Settings), settings_setup)
+            .add_systems(
+                Update,
+                (settings_action, rebind_key, update_setting_values)
+                    .chain()
+                    .in_set(MenuSet::Screens)
+                    .run_if(in_state(MenuScreen::Settings)),
+            )
+            .add_systems(
+                OnExit(MenuScreen::Settings),
+                (despawn_screen::<OnSettingsScreen>, save_user_settings),
+            );
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::GameConfig;
+use crate::game::input::{Action, MenuInput};
+use crate::ui::menu::{BackButton, SelectedOption};
+use crate::ui::navigation::activated;
+use crate::ui::settings::*;
+use crate::ui::widgets::spawn_button;
+use crate::user_settings::{UserSettings, UserSettingsPath};
+use bevy::prelude::*;
+
+pub fn settings_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let font = asset_server.load("fonts/font.ttf");
+    let text_style = TextStyle {
+        font_size: config.score_menu_text_size,
+        color: config.menu_text_color,
+        font: font.clone(),
+    };
+    let button_text_style = TextStyle {
+        color: Color::WHITE,
+        ..text_style.clone()
+    };
+    let row_style = Style {
+        align_items: AlignItems::Center,
+        margin: UiRect::vertical(Val::Px(4.0)),
+        ..default()
+    };
+    let label_style = Style {
+        width: Val::Px(200.0),
+        ..default()
+    };
+    let arrow_style = Style {
+        width: Val::Px(40.0),
+        height: Val::Px(36.0),
+        border: UiRect::all(Val::Px(config.focus_ring_width)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let value_style = Style {
+        width: Val::Px(160.0),
+        justify_content: JustifyContent::Center,
+        ..default()
+    };
+    let clear_button_style = Style {
+        width: Val::Px(90.0),
+        margin: UiRect::left(Val::Px(8.0)),
+        ..arrow_style.clone()
+    };
+    let wide_button_style = Style {
+        width: Val::Px(240.0),
+        height: Val::Px(36.0),
+        border: UiRect::all(Val::Px(config.focus_ring_width)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnSettingsScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::BLACK.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Settings".to_uppercase(),
+                            TextStyle {
+                                font_size: config.menu_title_size,
+                                ..text_style.clone()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+
+                    for setting in Setting::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: row_style.clone(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        setting.label().to_uppercase(),
+                                        text_style.clone(),
+                                    )
+                                    .with_style(label_style.clone()),
+                                );
+                                spawn_button(
+                                    parent,
+                                    &config,
+                                    arrow_style.clone(),
+                                    SettingsButtonAction::Previous(setting),
+                                    TextBundle::from_section("<", button_text_style.clone()),
+                                );
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: value_style.clone(),
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            TextBundle::from_section("", text_style.clone()),
+                                            SettingValue(setting),
+                                        ));
+                                    });
+                                spawn_button(
+                                    parent,
+                                    &config,
+                                    arrow_style.clone(),
+                                    SettingsButtonAction::Next(setting),
+                                    TextBundle::from_section(">", button_text_style.clone()),
+                                );
+                            });
+                    }
+
+                    for action in Action::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: row_style.clone(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        format!("{action:?}").to_uppercase(),
+                                        text_style.clone(),
+                                    )
+                                    .with_style(label_style.clone()),
+                                );
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: wide_button_style.clone(),
+                                            background_color: config.normal_button.into(),
+                                            ..default()
+                                        },
+                                        SettingsButtonAction::Rebind(action),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            TextBundle::from_section("", button_text_style.clone()),
+                                            BindingValue(action),
+                                        ));
+                                    });
+                                spawn_button(
+                                    parent,
+                                    &config,
+                                    clear_button_style.clone(),
+                                    SettingsButtonAction::ClearBinding(action),
+                                    TextBundle::from_section(
+                                        "Clear".to_uppercase(),
+                                        button_text_style.clone(),
+                                    ),
+                                );
+                            });
+                    }
+
+                    spawn_button(
+                        parent,
+                        &config,
+                        Style {
+                            margin: UiRect::all(Val::Px(12.0)),
+                            ..wide_button_style
+                        },
+                        BackButton,
+                        TextBundle::from_section("Back".to_uppercase(), button_text_style),
+                    );
+                });
+        });
+}
+
+pub fn settings_action(
+    interaction_query: Query<
+        (Ref<Interaction>, &SettingsButtonAction, Has<SelectedOption>),
+        With<Button>,
+    >,
+    mut menu_input: ResMut<MenuInput>,
+    mut settings: ResMut<UserSettings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut config: ResMut<GameConfig>,
+) {
+    // The next key goes to the action being rebound, it neither activates the
+    // focused button nor goes back.
+    if rebinding.0.is_some() {
+        menu_input.confirm = false;
+        menu_input.back = false;
+    }
+
+    for (interaction, action, selected) in &interaction_query {
+        if activated(&interaction, selected, &menu_input) {
+            match action {
+                SettingsButtonAction::Previous(setting) => settings.step(*setting, false),
+                SettingsButtonAction::Next(setting) => settings.step(*setting, true),
+                SettingsButtonAction::Rebind(action) => rebinding.0 = Some(*action),
+                SettingsButtonAction::ClearBinding(action) => {
+                    config.key_bindings.clear(*action);
+                    settings.key_bindings = Some(config.key_bindings.clone());
+                }
+            }
+        }
+    }
+}
+
+pub fn rebind_key(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut config: ResMut<GameConfig>,
+    mut settings: ResMut<UserSettings>,
+) {
+    // The key that started the rebinding is not the one to bind.
+    if rebinding.is_changed() {
+        return;
+    }
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(key) = keyboard_input.get_just_pressed().next().copied() else {
+        return;
+    };
+    rebinding.0 = None;
+    if key == KeyCode::Back {
+        return;
+    }
+
+    config.key_bindings.bind(action, key);
+    settings.key_bindings = Some(config.key_bindings.clone());
+}
+
+pub fn update_setting_values(
+    settings: Res<UserSettings>,
+    config: Res<GameConfig>,
+    rebinding: Res<Rebinding>,
+    mut setting_query: Query<(&mut Text, &SettingValue), Without<BindingValue>>,
+    mut binding_query: Query<(&mut Text, &BindingValue), Without<SettingValue>>,
+    added_query: Query<(), Added<SettingValue>>,
+) {
+    let changed = settings.is_changed() || config.is_changed() || rebinding.is_changed();
+    if !changed && added_query.is_empty() {
+        return;
+    }
+
+    for (mut text, SettingValue(setting)) in &mut setting_query {
+        text.sections[0].value = settings.describe(*setting).to_uppercase();
+    }
+    for (mut text, BindingValue(action)) in &mut binding_query {
+        let value = if rebinding.0 == Some(*action) {
+            "Press a key".to_string()
+        } else {
+            let keys = config.key_bindings.keys(*action);
+            if keys.is_empty() {
+                "None".to_string()
+            } else {
+                keys.iter()
+                    .map(|key| format!("{key:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        text.sections[0].value = value.to_uppercase();
+    }
+}
+
+pub fn save_user_settings(
+    settings: Res<UserSettings>,
+    path: Res<UserSettingsPath>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+    if let Some(path) = &path.0 {
+        if let Err(err) = settings.save(path) {
+            error!("Failed to save the settings to {}: {err}", path.display());
+        }
+    }
+}
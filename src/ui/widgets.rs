@@ -0,0 +1,112 @@
+use crate::config::GameConfig;
+use crate::ui::menu::BackButton;
+use bevy::prelude::*;
+
+/// Spawn the root of a screen, tagged with `marker`, holding a centered column
+/// filled by `children`.
+pub fn spawn_screen(
+    commands: &mut Commands,
+    marker: impl Component,
+    children: impl FnOnce(&mut ChildBuilder),
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::BLACK.into(),
+                    ..default()
+                })
+                .with_children(children);
+        });
+}
+
+pub fn spawn_title(
+    parent: &mut ChildBuilder,
+    config: &GameConfig,
+    font: Handle<Font>,
+    title: &str,
+) {
+    parent.spawn(
+        TextBundle::from_section(
+            title.to_uppercase(),
+            TextStyle {
+                font_size: config.menu_title_size,
+                color: config.menu_text_color,
+                font,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::all(Val::Px(30.0)),
+            ..default()
+        }),
+    );
+}
+
+/// Style of the large buttons of the menus.
+pub fn button_style(config: &GameConfig) -> Style {
+    Style {
+        width: Val::Px(config.button_width),
+        height: Val::Px(config.button_height),
+        margin: UiRect::all(Val::Px(config.button_margin)),
+        border: UiRect::all(Val::Px(config.focus_ring_width)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    config: &GameConfig,
+    style: Style,
+    action: impl Bundle,
+    text: TextBundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style,
+                background_color: config.normal_button.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text);
+        });
+}
+
+pub fn spawn_back_button(parent: &mut ChildBuilder, config: &GameConfig, font: Handle<Font>) {
+    spawn_button(
+        parent,
+        config,
+        button_style(config),
+        BackButton,
+        TextBundle::from_section(
+            "Back".to_uppercase(),
+            TextStyle {
+                font_size: config.text_button_size,
+                color: Color::WHITE,
+                font,
+            },
+        ),
+    );
+}
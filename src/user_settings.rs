@@ -0,0 +1,335 @@
+use crate::config::GameConfig;
+use crate::game::input::KeyBindings;
+use crate::storage;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "settings.ron";
+
+const VOLUME_STEP: f32 = 0.1;
+/// Bounds the fire rate of the last aliens of a wave.
+const MIN_ALIVE_SHARE: f32 = 0.05;
+
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Factor applied to the duration of an alien tick, lower is faster.
+    pub fn tick_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.8,
+        }
+    }
+
+    /// Factor applied to the probability that an alien shoots, given the share of the
+    /// aliens of the wave still alive.
+    ///
+    /// The formation keeps shooting as often as it loses aliens on normal, less often on
+    /// easy and more and more often on hard.
+    pub fn fire_factor(self, alive: f32) -> f32 {
+        let (factor, exponent) = match self {
+            Difficulty::Easy => (0.6, 0.5),
+            Difficulty::Normal => (1.0, 1.0),
+            Difficulty::Hard => (1.5, 1.5),
+        };
+        factor / alive.max(MIN_ALIVE_SHARE).powf(exponent)
+    }
+}
+
+/// Options chosen by the player on the settings screen, kept between sessions.
+#[derive(Resource, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Between 0 and 1, applied to the music and the sound effects.
+    pub master_volume: f32,
+    /// Between 0 and 1.
+    pub music_volume: f32,
+    /// Between 0 and 1.
+    pub sfx_volume: f32,
+    pub difficulty: Difficulty,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Pause the game when its window loses the focus or is minimised.
+    pub auto_pause: bool,
+    /// Replace the key bindings of the game config once the player changed them.
+    pub key_bindings: Option<KeyBindings>,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            difficulty: Difficulty::Normal,
+            fullscreen: false,
+            vsync: true,
+            auto_pause: true,
+            key_bindings: None,
+        }
+    }
+}
+
+/// A value of the settings screen that can be stepped through.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Setting {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Difficulty,
+    Fullscreen,
+    Vsync,
+    AutoPause,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 7] = [
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::SfxVolume,
+        Setting::Difficulty,
+        Setting::Fullscreen,
+        Setting::Vsync,
+        Setting::AutoPause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Volume",
+            Setting::MusicVolume => "Music",
+            Setting::SfxVolume => "Sounds",
+            Setting::Difficulty => "Difficulty",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::Vsync => "Vsync",
+            Setting::AutoPause => "Auto pause",
+        }
+    }
+}
+
+impl UserSettings {
+    /// Default location of the settings, in the configuration directory of the user.
+    pub fn default_path() -> Option<PathBuf> {
+        storage::config_path(FILE_NAME)
+    }
+
+    /// Load the settings stored at `path`, or the defaults if they are missing or corrupted.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let mut settings: Self = storage::load(path.as_ref(), "settings");
+        let defaults = Self::default();
+        // A hand-edited NaN would make it through a clamp.
+        let volume = |volume: f32, default: f32| {
+            if volume.is_finite() {
+                volume.clamp(0.0, 1.0)
+            } else {
+                default
+            }
+        };
+        settings.master_volume = volume(settings.master_volume, defaults.master_volume);
+        settings.music_volume = volume(settings.music_volume, defaults.music_volume);
+        settings.sfx_volume = volume(settings.sfx_volume, defaults.sfx_volume);
+        settings
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        storage::save(self, path.as_ref())
+    }
+
+    /// Move `setting` one step forward, or backward if `forward` is false.
+    pub fn step(&mut self, setting: Setting, forward: bool) {
+        let step_volume = |volume: f32| {
+            let step = if forward { VOLUME_STEP } else { -VOLUME_STEP };
+            // Round to avoid drifting away from the steps.
+            ((volume + step).clamp(0.0, 1.0) / VOLUME_STEP).round() * VOLUME_STEP
+        };
+        match setting {
+            Setting::MasterVolume => self.master_volume = step_volume(self.master_volume),
+            Setting::MusicVolume => self.music_volume = step_volume(self.music_volume),
+            Setting::SfxVolume => self.sfx_volume = step_volume(self.sfx_volume),
+            Setting::Difficulty => {
+                let count = Difficulty::ALL.len();
+                let index = Difficulty::ALL
+                    .iter()
+                    .position(|difficulty| *difficulty == self.difficulty)
+                    .unwrap_or_default();
+                let index = if forward {
+                    (index + 1) % count
+                } else {
+                    (index + count - 1) % count
+                };
+                self.difficulty = Difficulty::ALL[index];
+            }
+            Setting::Fullscreen => self.fullscreen = !self.fullscreen,
+            Setting::Vsync => self.vsync = !self.vsync,
+            Setting::AutoPause => self.auto_pause = !self.auto_pause,
+        }
+    }
+
+    pub fn describe(&self, setting: Setting) -> String {
+        let on_off = |value: bool| if value { "On" } else { "Off" }.to_string();
+        match setting {
+            Setting::MasterVolume => format!("{:.0}%", self.master_volume * 100.0),
+            Setting::MusicVolume => format!("{:.0}%", self.music_volume * 100.0),
+            Setting::SfxVolume => format!("{:.0}%", self.sfx_volume * 100.0),
+            Setting::Difficulty => format!("{:?}", self.difficulty),
+            Setting::Fullscreen => on_off(self.fullscreen),
+            Setting::Vsync => on_off(self.vsync),
+            Setting::AutoPause => on_off(self.auto_pause),
+        }
+    }
+}
+
+/// Where the settings are saved, if anywhere.
+#[derive(Resource)]
+pub struct UserSettingsPath(pub Option<PathBuf>);
+
+/// Load the settings of the player and apply them whenever they change.
+pub struct UserSettingsPlugin;
+
+impl Plugin for UserSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let path = UserSettings::default_path();
+        let settings = path.as_ref().map(UserSettings::load).unwrap_or_default();
+
+        app.insert_resource(settings)
+            .insert_resource(UserSettingsPath(path))
+            .add_systems(Startup, apply_key_bindings)
+            .add_systems(
+                Update,
+                apply_display_settings.run_if(resource_changed::<UserSettings>()),
+            );
+    }
+}
+
+/// Use the keys rebound by the player instead of the ones of the config, unless some
+/// of them are bound to several actions.
+pub fn apply_key_bindings(settings: Res<UserSettings>, mut config: ResMut<GameConfig>) {
+    let Some(key_bindings) = &settings.key_bindings else {
+        return;
+    };
+    let conflicts = key_bindings.conflicts();
+    if !conflicts.is_empty() {
+        for (key, actions) in conflicts {
+            warn!("Rebound key {key:?} is bound to several actions: {actions:?}");
+        }
+        warn!("Keeping the key bindings of the config");
+        return;
+    }
+    config.key_bindings = key_bindings.clone();
+}
+
+pub fn apply_display_settings(
+    settings: Res<UserSettings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    window.mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    window.present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::input::Action;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::fs;
+
+    fn load_from(name: &str, contents: &str) -> UserSettings {
+        let dir = std::env::temp_dir().join(format!("invaders-{name}-{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, contents).unwrap();
+        let settings = UserSettings::load(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        settings
+    }
+
+    #[test]
+    fn load_replaces_invalid_volumes() {
+        let settings = load_from(
+            "volumes",
+            "(master_volume: NaN, music_volume: inf, sfx_volume: 2.0)",
+        );
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.music_volume, 1.0);
+        assert_eq!(settings.sfx_volume, 1.0);
+
+        let settings = load_from("negative", "(master_volume: -1.0, sfx_volume: 0.3)");
+        assert_eq!(settings.master_volume, 0.0);
+        assert_eq!(settings.sfx_volume, 0.3);
+    }
+
+    fn applied_key_bindings(key_bindings: KeyBindings) -> KeyBindings {
+        let mut world = World::new();
+        world.init_resource::<GameConfig>();
+        world.insert_resource(UserSettings {
+            key_bindings: Some(key_bindings),
+            ..default()
+        });
+        world.run_system_once(apply_key_bindings);
+        world.resource::<GameConfig>().key_bindings.clone()
+    }
+
+    #[test]
+    fn apply_rebound_keys() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.bind(Action::Fire, KeyCode::Up);
+        assert_eq!(applied_key_bindings(key_bindings.clone()), key_bindings);
+    }
+
+    #[test]
+    fn conflicting_rebound_keys_keep_the_config_bindings() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.0.insert(Action::Fire, vec![KeyCode::Left]);
+        assert_eq!(
+            applied_key_bindings(key_bindings),
+            GameConfig::default().key_bindings
+        );
+    }
+
+    #[test]
+    fn save_and_load_custom_key_bindings() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings
+            .0
+            .insert(Action::Fire, vec![KeyCode::Up, KeyCode::W]);
+        let settings = UserSettings {
+            master_volume: 0.5,
+            difficulty: Difficulty::Hard,
+            auto_pause: false,
+            key_bindings: Some(key_bindings),
+            ..default()
+        };
+
+        let dir = std::env::temp_dir().join(format!("invaders-settings-{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        settings.save(&path).unwrap();
+        let loaded = UserSettings::load(&path);
+        let corrupted = path.with_extension("ron.corrupted").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!corrupted);
+        assert_eq!(loaded, settings);
+    }
+}